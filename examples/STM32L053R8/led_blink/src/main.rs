@@ -63,13 +63,12 @@ fn main() -> ! {
     let mut q = mt.msgq::<Toggle>(4);
     let (snd, rcv) = q.ch();
 
-    // shared message sender
-    let s_snd = mt.share(snd);
-    let sc_snd0 = s_snd.ch();
-    let sc_snd1 = s_snd.ch();
+    // message senders
+    let snd0 = snd.clone();
+    let snd1 = snd;
 
-    mt.register(0, 1, 256, || _led_tim0(timer2, sc_snd0, cnt0, div0));
-    mt.register(1, 1, 256, || _led_tim1(systcnt, sc_snd1, cnt1, div1));
+    mt.register(0, 1, 256, || _led_tim0(timer2, snd0, cnt0, div0));
+    mt.register(1, 1, 256, || _led_tim1(systcnt, snd1, cnt1, div1));
     mt.register(2, 2, 256, || _led_tgl(pa5, rcv)); // blink and pause
 
     // ----- ----- ----- ----- -----
@@ -100,7 +99,7 @@ fn _led_tgl(mut pa5: gpioa::PA5<Output<PushPull>>, mut rcv: MTMsgReceiver<Toggle
     }
 }
 
-fn _led_tim0(mut timer2: Timer<pac::TIM2>, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32, div: u32)
+fn _led_tim0(mut timer2: Timer<pac::TIM2>, mut snd: MTMsgSender<Toggle>, cnt: u32, div: u32)
 {
     loop {
         Minimult::idle();
@@ -113,12 +112,11 @@ fn _led_tim0(mut timer2: Timer<pac::TIM2>, sc_snd: MTSharedCh<MTMsgSender<Toggle
 
         //
 
-        let mut snd = sc_snd.touch();
         snd.send(Toggle(cnt, div));
     }
 }
 
-fn _led_tim1(timcnt: u32, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32, div: u32)
+fn _led_tim1(timcnt: u32, mut snd: MTMsgSender<Toggle>, cnt: u32, div: u32)
 {
     loop {
         for _ in 0..timcnt {
@@ -127,7 +125,6 @@ fn _led_tim1(timcnt: u32, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32, div
 
         //
 
-        let mut snd = sc_snd.touch();
         snd.send(Toggle(cnt, div));
     }
 }
@@ -82,13 +82,12 @@ fn main() -> ! {
     let mut q = mt.msgq::<Toggle>(4);
     let (snd, rcv) = q.ch();
 
-    // shared message sender
-    let s_snd = mt.share(snd);
-    let sc_snd0 = s_snd.ch();
-    let sc_snd1 = s_snd.ch();
+    // message senders
+    let snd0 = snd.clone();
+    let snd1 = snd;
 
-    mt.register(0, 1, 256, || _led_tim0(timer0, sc_snd0, cnt0, div0));
-    mt.register(1, 1, 256, || _led_tim1(systcnt, sc_snd1, cnt1, div1));
+    mt.register(0, 1, 256, || _led_tim0(timer0, snd0, cnt0, div0));
+    mt.register(1, 1, 256, || _led_tim1(systcnt, snd1, cnt1, div1));
     mt.register(2, 2, 256, || _led_tgl(p0, rcv)); // blink and pause
 
     // ----- ----- ----- ----- -----
@@ -119,7 +118,7 @@ fn _led_tgl(p0: P0_S, mut rcv: MTMsgReceiver<Toggle>)
     }
 }
 
-fn _led_tim0(timer0: TIMER0_S, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32, div: u32)
+fn _led_tim0(timer0: TIMER0_S, mut snd: MTMsgSender<Toggle>, cnt: u32, div: u32)
 {
     loop {
         Minimult::idle();
@@ -132,12 +131,11 @@ fn _led_tim0(timer0: TIMER0_S, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32
 
         //
 
-        let mut snd = sc_snd.touch();
         snd.send(Toggle(cnt, div));
     }
 }
 
-fn _led_tim1(timcnt: u32, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32, div: u32)
+fn _led_tim1(timcnt: u32, mut snd: MTMsgSender<Toggle>, cnt: u32, div: u32)
 {
     loop {
         for _ in 0..timcnt {
@@ -146,7 +144,6 @@ fn _led_tim1(timcnt: u32, sc_snd: MTSharedCh<MTMsgSender<Toggle>>, cnt: u32, div
 
         //
 
-        let mut snd = sc_snd.touch();
         snd.send(Toggle(cnt, div));
     }
 }
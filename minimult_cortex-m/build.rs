@@ -15,4 +15,10 @@ fn main() {
         println!("cargo:rustc-link-lib=static={}", name);
         println!("cargo:rustc-link-search={}", out_dir.display());
     }
+
+    println!("cargo:rustc-check-cfg=cfg(armv7m)");
+
+    if target.starts_with("thumbv7m") || target.starts_with("thumbv7em") {
+        println!("cargo:rustc-cfg=armv7m");
+    }
 }
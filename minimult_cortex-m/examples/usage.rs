@@ -25,9 +25,11 @@ fn main() -> !
     let shch1 = sh.ch();
     let shch2 = sh.ch();
 
+    let cv = mt.condvar();
+
     mt.register(0/*tid*/, 1, 256, || task0(snd));
-    mt.register(1/*tid*/, 1, 256, || task1(rcv, shch1));
-    mt.register(2/*tid*/, 1, 256, || task2(shch2));
+    mt.register(1/*tid*/, 1, 256, || task1(rcv, shch1, &cv));
+    mt.register(2/*tid*/, 1, 256, || task2(shch2, &cv));
 
     // SysTick settings
     let cmperi = Peripherals::take().unwrap();
@@ -46,6 +48,7 @@ fn main() -> !
     //drop(sh);
     //drop(shch1);
     //drop(shch2);
+    //drop(cv);
     
     hprintln!("Minimult run").unwrap();
     mt.run()
@@ -67,7 +70,7 @@ fn task0(mut snd: MTMsgSender<u32>)
     }
 }
 
-fn task1(mut rcv: MTMsgReceiver<u32>, shch: MTSharedCh<u32>)
+fn task1(mut rcv: MTMsgReceiver<u32>, shch: MTSharedCh<u32>, cv: &MTCondvar)
 {
     for i in 0.. {
         let vrcv = rcv.receive();
@@ -76,18 +79,21 @@ fn task1(mut rcv: MTMsgReceiver<u32>, shch: MTSharedCh<u32>)
         hprintln!("task1 touch {}", vrcv).unwrap();
         let mut vtouch = shch.touch();
         *vtouch = vrcv;
+        drop(vtouch);
+
+        cv.notify_all();
     }
 }
 
-fn task2(shch: MTSharedCh<u32>)
+fn task2(shch: MTSharedCh<u32>, cv: &MTCondvar)
 {
     let mut j = 0;
 
     while j < 50 {
-        let vlook = shch.look();
+        let vlook = cv.wait_while(shch.look(), |v| *v == j);
 
-        assert!(j <= *vlook);
-        //hprintln!("task2 look {}", *vlook).unwrap(); // many lines printed
+        assert!(j < *vlook);
+        hprintln!("task2 look {}", *vlook).unwrap();
         j = *vlook;
     }
 
@@ -0,0 +1,69 @@
+use core::marker::PhantomData;
+
+use crate::minimult::Minimult;
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Barrier which synchronizes a number of tasks
+pub struct MTBarrier<'a>
+{
+    num: usize,
+    arrived: usize,
+    generation: MTEvent,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl Sync for MTBarrier<'_> {}
+
+impl<'a> MTBarrier<'a>
+{
+    pub(crate) fn new(num: usize) -> MTBarrier<'a> // NOTE: lifetime safety correctness
+    {
+        assert!(num > 0,
+                "barrier: zero participants");
+
+        MTBarrier {
+            num,
+            arrived: 0,
+            generation: MTEvent::new(0),
+            phantom: PhantomData
+        }
+    }
+}
+
+impl MTBarrier<'_>
+{
+    /// Waits for all of the participants.
+    /// * Blocks until the number of tasks given at the creation have called `wait`.
+    /// * Returns `true` for the leader, which is the last arrived task, and `false` for the others.
+    /// * The barrier is reused for the next phase after all of the tasks are released.
+    pub fn wait(&self) -> bool
+    {
+        let b = (self as *const Self) as *mut Self; // NOTE: mutability conversion
+        let b = unsafe { b.as_mut().bk_unwrap() };
+
+        let (gen, leader) = cortex_m::interrupt::free(|_| {
+            let gen = b.generation.cnt();
+
+            b.arrived += 1;
+
+            if b.arrived == b.num {
+                b.arrived = 0;
+                b.generation.incr();
+                (gen, true)
+            }
+            else {
+                (gen, false)
+            }
+        });
+
+        if leader {
+            Minimult::signal(&b.generation);
+        }
+        else {
+            Minimult::wait(&b.generation, MTEventCond::NotEqual(gen));
+        }
+
+        leader
+    }
+}
@@ -34,34 +34,32 @@ where I: Integer + Into<usize> + Copy, K: Ord
         }
     }
 
-    fn up_bheap(&mut self)
+    fn up_bheap(&mut self, pos: I)
     {
         let two = I::one() + I::one();
 
-        if self.n_bheap > I::zero() {
-            let mut pos = self.n_bheap - I::one();
-
-            while pos > I::zero() {
-                let parent = (pos - I::one()) / two;
+        let mut pos = pos;
 
-                let key_pos = self.array.read(pos).1;
-                let key_parent = self.array.read(parent).1;
+        while pos > I::zero() {
+            let parent = (pos - I::one()) / two;
 
-                if key_pos >= key_parent {
-                    break;
-                }
+            let key_pos = self.array.read(pos).1;
+            let key_parent = self.array.read(parent).1;
 
-                self.replace(pos, parent);
-                pos = parent;
+            if key_pos >= key_parent {
+                break;
             }
+
+            self.replace(pos, parent);
+            pos = parent;
         }
     }
 
-    fn down_bheap(&mut self)
+    fn down_bheap(&mut self, pos: I)
     {
         let two = I::one() + I::one();
 
-        let mut pos = I::zero();
+        let mut pos = pos;
 
         while pos < self.n_bheap / two {
             let child0 = (pos * two) + I::one();
@@ -93,6 +91,37 @@ where I: Integer + Into<usize> + Copy, K: Ord
         }
     }
 
+    fn update_bheap(&mut self, pos: I)
+    {
+        let two = I::one() + I::one();
+
+        if pos > I::zero() {
+            let parent = (pos - I::one()) / two;
+
+            if self.array.read(pos).1 < self.array.read(parent).1 {
+                self.up_bheap(pos);
+                return;
+            }
+        }
+
+        self.down_bheap(pos);
+    }
+
+    fn position(&self, id: I) -> Option<I>
+    {
+        let pos_e = self.n_bheap + self.n_flist;
+
+        let mut pos = I::zero();
+        while pos < pos_e {
+            if self.array.read(pos).0 == id {
+                return Some(pos);
+            }
+            pos = pos + I::one();
+        }
+
+        None
+    }
+
     fn flist_to_bheap(&mut self, pos: I)
     {
         bk_assert!(pos >= self.n_bheap);
@@ -105,45 +134,84 @@ where I: Integer + Into<usize> + Copy, K: Ord
         self.n_flist = self.n_flist - I::one();
         self.n_bheap = self.n_bheap + I::one();
 
-        self.up_bheap();
+        self.up_bheap(self.n_bheap - I::one());
     }
 
-    pub(crate) fn add_bheap(&mut self, id: I, key: K)
+    fn bheap_to_flist_h_pos(&mut self, pos: I)
+    {
+        bk_assert!(pos < self.n_bheap);
+
+        // replace bheap pos <=> bheap tail
+        let pos1 = self.n_bheap - I::one();
+        self.replace(pos, pos1);
+
+        // bheap tail <=> flist head
+        self.n_flist = self.n_flist + I::one();
+        self.n_bheap = self.n_bheap - I::one();
+
+        if pos < self.n_bheap {
+            self.update_bheap(pos);
+        }
+    }
+
+    pub(crate) fn add_flist(&mut self, id: I, key: K)
     {
         // add flist tail
         let pos = self.n_bheap + self.n_flist;
         self.array.write(pos, (id, key));
         self.n_flist = self.n_flist + I::one();
+    }
+
+    pub(crate) fn add_bheap(&mut self, id: I, key: K)
+    {
+        self.add_flist(id, key);
 
         // flist tail => bheap
+        let pos = self.n_bheap + self.n_flist - I::one();
         self.flist_to_bheap(pos);
     }
 
+    pub(crate) fn flist_h_to_bheap(&mut self, key: K) -> Option<I>
+    {
+        if self.n_flist > I::zero() {
+            let id = self.array.read(self.n_bheap).0;
+            self.array.write(self.n_bheap, (id, key));
+
+            self.flist_to_bheap(self.n_bheap);
+
+            Some(id)
+        }
+        else {
+            None
+        }
+    }
+
     pub(crate) fn bheap_h_to_flist_h(&mut self)
     {
         bk_assert!(self.n_bheap > I::zero());
-        
-        // replace bheap head <=> bheap tail
-        let pos1 = self.n_bheap - I::one();
-        self.replace(I::zero(), pos1);
 
-        // bheap tail <=> flist head
-        self.n_flist = self.n_flist + I::one();
-        self.n_bheap = self.n_bheap - I::one();
+        self.bheap_to_flist_h_pos(I::zero());
+    }
+
+    pub(crate) fn bheap_to_flist_h(&mut self, id: I)
+    {
+        let pos = self.position(id);
+        bk_assert!(pos.is_some());
+        let pos = pos.unwrap();
 
-        self.down_bheap();
+        self.bheap_to_flist_h_pos(pos);
     }
 
-    pub(crate) fn round_bheap_h(&mut self)
+    pub(crate) fn round_bheap(&mut self, id: I)
     {
-        self.bheap_h_to_flist_h();
+        self.bheap_to_flist_h(id);
 
         self.flist_to_bheap(self.n_bheap);
     }
 
-    pub(crate) fn remove_bheap_h(&mut self)
+    pub(crate) fn remove_bheap(&mut self, id: I)
     {
-        self.bheap_h_to_flist_h();
+        self.bheap_to_flist_h(id);
 
         // replace flist head <=> flist tail
         let pos1 = self.n_bheap + self.n_flist - I::one();
@@ -153,6 +221,22 @@ where I: Integer + Into<usize> + Copy, K: Ord
         self.n_flist = self.n_flist - I::one();
     }
 
+    pub(crate) fn set_key(&mut self, id: I, key: K) -> bool
+    {
+        if let Some(pos) = self.position(id) {
+            self.array.write(pos, (id, key));
+
+            if pos < self.n_bheap {
+                self.update_bheap(pos);
+            }
+
+            true
+        }
+        else {
+            false
+        }
+    }
+
     pub(crate) fn bheap_h(&self) -> Option<I>
     {
         if self.n_bheap > I::zero() {
@@ -163,8 +247,8 @@ where I: Integer + Into<usize> + Copy, K: Ord
         }
     }
 
-    pub(crate) fn flist_scan<F>(&mut self, to_bheap: F)
-    where F: Fn(I) -> bool
+    pub(crate) fn flist_scan<F>(&mut self, mut to_bheap: F)
+    where F: FnMut(I) -> bool
     {
         let pos_b = self.n_bheap;
         let pos_e = pos_b + self.n_flist;
@@ -0,0 +1,236 @@
+use core::marker::PhantomData;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::memory::MTRawArray;
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+//
+
+fn wrap_inc(x: usize, bound: usize) -> usize
+{
+    let y = x + 1;
+    if y < bound {y} else {0}
+}
+
+//
+
+/// Error of a broadcast receiver which has fallen behind
+/// * The value is the number of messages overwritten before received.
+#[derive(Debug)]
+pub struct MTLagged(pub usize);
+
+//
+
+/// Broadcast channel from one sender to many receivers
+pub struct MTBroadcast<'a, M>
+{
+    mem: MTRawArray<M>,
+    wr_idx: usize,
+    filled: bool,
+    wr_seq: MTEvent,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a, M: Clone> MTBroadcast<'a, M>
+{
+    pub(crate) fn new(mem: MTRawArray<M>) -> MTBroadcast<'a, M> // NOTE: lifetime safety correctness
+    {
+        assert!(mem.len() > 0,
+                "broadcast: zero length");
+
+        MTBroadcast {
+            mem,
+            wr_idx: 0,
+            filled: false,
+            wr_seq: MTEvent::new(0),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets a sending channel.
+    /// * Returns the sender, from which receivers are subscribed.
+    pub fn ch<'b>(&'b mut self) -> MTBroadcastSender<'a, 'b, M>
+    {
+        MTBroadcastSender {
+            b: self,
+            phantom: PhantomData
+        }
+    }
+}
+
+//
+
+/// Broadcast sending channel
+pub struct MTBroadcastSender<'a, 'b, M>
+{
+    b: *mut MTBroadcast<'a, M>,
+    phantom: PhantomData<&'b ()>
+}
+
+unsafe impl<M: Send> Send for MTBroadcastSender<'_, '_, M> {}
+
+impl<'a, 'b, M: Clone> MTBroadcastSender<'a, 'b, M>
+{
+    /// Subscribes a receiving channel.
+    /// * Returns the receiver, which receives messages sent after the subscription.
+    pub fn subscribe(&self) -> MTBroadcastReceiver<'a, 'b, M>
+    {
+        subscribe(self.b)
+    }
+
+    /// Sends a message to all of the receivers.
+    /// * `msg` - the message to be sent.
+    /// * Overwrites the oldest message if the ring is full, so never blocks for receivers falling behind.
+    /// * Never blocks.
+    /// * Can be called from interrupt handlers.
+    pub fn send(&mut self, msg: M)
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        let old = cortex_m::interrupt::free(|_| {
+            let old = if b.filled {
+                Some(b.mem.read(b.wr_idx))
+            }
+            else {
+                None
+            };
+
+            b.mem.write_volatile(b.wr_idx, msg);
+
+            b.wr_idx = wrap_inc(b.wr_idx, b.mem.len());
+            if b.wr_idx == 0 {
+                b.filled = true;
+            }
+
+            b.wr_seq.incr(); // NOTE: wrapping-around is intended
+
+            old
+        });
+
+        drop(old); // out of the critical section
+
+        Minimult::signal(&b.wr_seq);
+    }
+}
+
+fn subscribe<'a, 'b, M>(b: *mut MTBroadcast<'a, M>) -> MTBroadcastReceiver<'a, 'b, M>
+{
+    let (rd_idx, rd_seq) = {
+        let b = unsafe { b.as_ref().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| (b.wr_idx, b.wr_seq.cnt()))
+    };
+
+    MTBroadcastReceiver {
+        b,
+        rd_idx,
+        rd_seq,
+        phantom: PhantomData
+    }
+}
+
+//
+
+/// Broadcast receiving channel
+pub struct MTBroadcastReceiver<'a, 'b, M>
+{
+    b: *mut MTBroadcast<'a, M>,
+    rd_idx: usize,
+    rd_seq: usize,
+    phantom: PhantomData<&'b ()>
+}
+
+unsafe impl<M: Send + Sync> Send for MTBroadcastReceiver<'_, '_, M> {}
+
+impl<'a, 'b, M: Clone> MTBroadcastReceiver<'a, 'b, M>
+{
+    /// Subscribes another receiving channel.
+    /// * Returns the receiver, which receives messages sent after the subscription.
+    pub fn subscribe(&self) -> MTBroadcastReceiver<'a, 'b, M>
+    {
+        subscribe(self.b)
+    }
+
+    /// Gets if there are messages not yet received.
+    /// * Returns the number of the messages, which may exceed the ring length if lagged.
+    pub fn available(&self) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        b.wr_seq.cnt().wrapping_sub(self.rd_seq)
+    }
+
+    /// Receives a message.
+    /// * Returns the received message.
+    /// * Blocks if there is no message not yet received.
+    /// * Returns `Err(MTLagged(n))` if `n` messages have been overwritten before received,
+    ///   then the next call receives the oldest message remaining.
+    pub fn receive(&mut self) -> Result<M, MTLagged>
+    {
+        loop {
+            if let Some(r) = self.try_receive() {
+                return r;
+            }
+
+            let b = unsafe { self.b.as_mut().bk_unwrap() };
+            Minimult::wait(&b.wr_seq, MTEventCond::NotEqual(self.rd_seq));
+        }
+    }
+
+    /// Receives a message with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the received message, or `Err(MTLagged(n))` as `receive` does.
+    /// * Blocks if there is no message not yet received.
+    /// * Returns `Err(MTTimeout)` if no message is sent within the timeout.
+    pub fn receive_timeout(&mut self, ticks: usize) -> Result<Result<M, MTLagged>, MTTimeout>
+    {
+        let from = Minimult::tick_count();
+
+        loop {
+            if let Some(r) = self.try_receive() {
+                return Ok(r);
+            }
+
+            let b = unsafe { self.b.as_mut().bk_unwrap() };
+            if !Minimult::wait_timeout(&b.wr_seq, MTEventCond::NotEqual(self.rd_seq), from, ticks) {
+                return Err(MTTimeout(()));
+            }
+        }
+    }
+
+    /// Tries to receive a message.
+    /// * Returns the received message, or `Err(MTLagged(n))` as `receive` does, in `Option`.
+    /// * Gets `None` if there is no message not yet received.
+    /// * Never blocks.
+    /// * Can be called from interrupt handlers.
+    /// * The message is cloned in a critical section, so that the sender is never blocked.
+    pub fn try_receive(&mut self) -> Option<Result<M, MTLagged>>
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| {
+            let wr_seq = b.wr_seq.cnt();
+            let len = b.mem.len();
+            let avail = wr_seq.wrapping_sub(self.rd_seq);
+
+            if avail == 0 {
+                None
+            }
+            else if avail > len {
+                self.rd_idx = b.wr_idx;
+                self.rd_seq = wr_seq.wrapping_sub(len);
+
+                Some(Err(MTLagged(avail - len)))
+            }
+            else {
+                let msg = b.mem.refer(self.rd_idx).clone();
+
+                self.rd_idx = wrap_inc(self.rd_idx, len);
+                self.rd_seq = self.rd_seq.wrapping_add(1);
+
+                Some(Ok(msg))
+            }
+        })
+    }
+}
@@ -0,0 +1,486 @@
+use core::marker::PhantomData;
+use core::convert::Infallible;
+
+use crate::minimult::Minimult;
+use crate::memory::MTRawArray;
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+//
+
+const LEN_PREFIX: usize = 2;
+
+struct MTByteRing
+{
+    mem: MTRawArray<u8>,
+    wr_idx: usize,
+    rd_idx: usize,
+    byte_cnt: MTEvent
+}
+
+impl MTByteRing
+{
+    fn new(mem: MTRawArray<u8>) -> MTByteRing
+    {
+        MTByteRing {
+            mem,
+            wr_idx: 0,
+            rd_idx: 0,
+            byte_cnt: MTEvent::new(0)
+        }
+    }
+
+    fn len(&self) -> usize
+    {
+        self.mem.len()
+    }
+
+    fn available(&self) -> usize
+    {
+        self.byte_cnt.cnt()
+    }
+
+    fn vacant(&self) -> usize
+    {
+        self.len() - self.byte_cnt.cnt()
+    }
+
+    fn wait_available(&self, n: usize)
+    {
+        while self.available() < n {
+            Minimult::wait(&self.byte_cnt, MTEventCond::GreaterThan(n - 1));
+        }
+    }
+
+    fn wait_vacant(&self, n: usize)
+    {
+        while self.vacant() < n {
+            Minimult::wait(&self.byte_cnt, MTEventCond::LessThan(self.len() - n + 1));
+        }
+    }
+
+    fn copy_in(&mut self, idx: usize, data: &[u8]) -> usize
+    {
+        let mut idx = idx;
+
+        for b in data {
+            self.mem.write_volatile(idx, *b);
+            idx += 1;
+            if idx == self.len() {
+                idx = 0;
+            }
+        }
+
+        idx
+    }
+
+    fn copy_out(&self, idx: usize, data: &mut [u8]) -> usize
+    {
+        let mut idx = idx;
+
+        for b in data {
+            *b = self.mem.read_volatile(idx);
+            idx += 1;
+            if idx == self.len() {
+                idx = 0;
+            }
+        }
+
+        idx
+    }
+
+    fn put(&mut self, data: &[u8])
+    {
+        self.wr_idx = self.copy_in(self.wr_idx, data); // NOTE: volatile access might be necessary
+
+        self.byte_cnt.add(data.len());
+        Minimult::signal(&self.byte_cnt);
+    }
+
+    fn get(&mut self, data: &mut [u8])
+    {
+        self.rd_idx = self.copy_out(self.rd_idx, data); // NOTE: volatile access might be necessary
+
+        self.byte_cnt.sub(data.len());
+        Minimult::signal(&self.byte_cnt);
+    }
+
+    fn skip(&mut self, n: usize)
+    {
+        self.rd_idx = (self.rd_idx + n) % self.len();
+
+        self.byte_cnt.sub(n);
+        Minimult::signal(&self.byte_cnt);
+    }
+}
+
+//
+
+/// Stream buffer of bytes for task-to-task communication
+pub struct MTStreamBuffer<'a>
+{
+    ring: MTByteRing,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a> MTStreamBuffer<'a>
+{
+    pub(crate) fn new(mem: MTRawArray<u8>) -> MTStreamBuffer<'a> // NOTE: lifetime safety correctness
+    {
+        assert!(mem.len() > 0,
+                "stream buffer: zero length");
+
+        MTStreamBuffer {
+            ring: MTByteRing::new(mem),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets writing and reading channels.
+    /// * Returns a tuple of the writer and reader pair.
+    pub fn ch<'b>(&'b mut self) -> (MTStreamWriter<'a, 'b>, MTStreamReader<'a, 'b>)
+    {
+        (
+            MTStreamWriter {
+                b: self,
+                phantom: PhantomData
+            },
+            MTStreamReader {
+                b: self,
+                phantom: PhantomData
+            }
+        )
+    }
+}
+
+/// Stream buffer writing channel
+pub struct MTStreamWriter<'a, 'b>
+{
+    b: *mut MTStreamBuffer<'a>,
+    phantom: PhantomData<&'b ()>
+}
+
+unsafe impl Send for MTStreamWriter<'_, '_> {}
+
+impl MTStreamWriter<'_, '_>
+{
+    /// Gets if there are vacant bytes.
+    /// * Returns the number of vacant bytes.
+    pub fn vacant(&self) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        b.ring.vacant()
+    }
+
+    /// Writes bytes.
+    /// * `data` - the bytes to be written.
+    /// * Returns the number of written bytes, which may be less than the length of `data`.
+    /// * Blocks if there is no vacant byte, unless `data` is empty.
+    pub fn write(&mut self, data: &[u8]) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        if data.is_empty() {
+            return 0;
+        }
+
+        b.ring.wait_vacant(1);
+
+        let n = data.len().min(b.ring.vacant());
+        b.ring.put(&data[..n]);
+
+        n
+    }
+}
+
+impl embedded_io::ErrorType for MTStreamWriter<'_, '_>
+{
+    type Error = Infallible;
+}
+
+impl embedded_io::Write for MTStreamWriter<'_, '_>
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error>
+    {
+        Ok(MTStreamWriter::write(self, buf))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error>
+    {
+        Ok(())
+    }
+}
+
+/// Stream buffer reading channel
+pub struct MTStreamReader<'a, 'b>
+{
+    b: *mut MTStreamBuffer<'a>,
+    phantom: PhantomData<&'b ()>
+}
+
+unsafe impl Send for MTStreamReader<'_, '_> {}
+
+impl MTStreamReader<'_, '_>
+{
+    /// Gets if there are available bytes.
+    /// * Returns the number of available bytes.
+    pub fn available(&self) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        b.ring.available()
+    }
+
+    /// Reads bytes.
+    /// * `data` - the buffer to store the read bytes.
+    /// * Returns the number of read bytes, which may be less than the length of `data`.
+    /// * Blocks if there is no available byte, unless `data` is empty.
+    pub fn read(&mut self, data: &mut [u8]) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        if data.is_empty() {
+            return 0;
+        }
+
+        b.ring.wait_available(1);
+
+        let n = data.len().min(b.ring.available());
+        b.ring.get(&mut data[..n]);
+
+        n
+    }
+}
+
+impl embedded_io::ErrorType for MTStreamReader<'_, '_>
+{
+    type Error = Infallible;
+}
+
+impl embedded_io::Read for MTStreamReader<'_, '_>
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+    {
+        Ok(MTStreamReader::read(self, buf))
+    }
+}
+
+//
+
+/// Error of message buffer channels through `embedded-io`
+#[derive(Debug)]
+pub enum MTFrameError
+{
+    /// A frame to be written is longer than the maximum length.
+    TooLong,
+    /// A buffer to read is shorter than the next frame, which is kept unread.
+    TooShort
+}
+
+impl embedded_io::Error for MTFrameError
+{
+    fn kind(&self) -> embedded_io::ErrorKind
+    {
+        embedded_io::ErrorKind::InvalidInput
+    }
+}
+
+//
+
+/// Message buffer of variable-length byte frames for task-to-task communication
+pub struct MTMessageBuffer<'a>
+{
+    ring: MTByteRing,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a> MTMessageBuffer<'a>
+{
+    pub(crate) fn new(mem: MTRawArray<u8>) -> MTMessageBuffer<'a> // NOTE: lifetime safety correctness
+    {
+        assert!(mem.len() > LEN_PREFIX,
+                "message buffer: length {} too short", mem.len());
+
+        MTMessageBuffer {
+            ring: MTByteRing::new(mem),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets writing and reading channels.
+    /// * Returns a tuple of the writer and reader pair.
+    pub fn ch<'b>(&'b mut self) -> (MTMessageWriter<'a, 'b>, MTMessageReader<'a, 'b>)
+    {
+        (
+            MTMessageWriter {
+                b: self,
+                phantom: PhantomData
+            },
+            MTMessageReader {
+                b: self,
+                phantom: PhantomData
+            }
+        )
+    }
+}
+
+/// Message buffer writing channel
+pub struct MTMessageWriter<'a, 'b>
+{
+    b: *mut MTMessageBuffer<'a>,
+    phantom: PhantomData<&'b ()>
+}
+
+unsafe impl Send for MTMessageWriter<'_, '_> {}
+
+impl MTMessageWriter<'_, '_>
+{
+    /// Gets the maximum length of a frame which can be written.
+    /// * Returns the number of bytes, which is the buffer length minus a 2-byte length prefix.
+    pub fn max_len(&self) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        (b.ring.len() - LEN_PREFIX).min(u16::MAX as usize)
+    }
+
+    /// Writes a frame.
+    /// * `frame` - the bytes to be written as one frame.
+    /// * Blocks if there are not enough vacant bytes for the frame.
+    pub fn write(&mut self, frame: &[u8])
+    {
+        assert!(frame.len() <= self.max_len(),
+                "message buffer: frame {} bytes over maximum", frame.len());
+
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        b.ring.wait_vacant(LEN_PREFIX + frame.len());
+
+        let prefix = (frame.len() as u16).to_le_bytes();
+        b.ring.wr_idx = b.ring.copy_in(b.ring.wr_idx, &prefix);
+        b.ring.wr_idx = b.ring.copy_in(b.ring.wr_idx, frame);
+
+        // publish the prefix and the frame at once
+        b.ring.byte_cnt.add(LEN_PREFIX + frame.len());
+        Minimult::signal(&b.ring.byte_cnt);
+    }
+}
+
+impl embedded_io::ErrorType for MTMessageWriter<'_, '_>
+{
+    type Error = MTFrameError;
+}
+
+impl embedded_io::Write for MTMessageWriter<'_, '_>
+{
+    /// Writes `buf` as one frame, or gets `Err(MTFrameError::TooLong)` if `buf` is longer than `max_len`.
+    /// Gets `Ok(0)` without blocking nor writing a frame if `buf` is empty.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error>
+    {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if buf.len() > self.max_len() {
+            return Err(MTFrameError::TooLong);
+        }
+
+        MTMessageWriter::write(self, buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error>
+    {
+        Ok(())
+    }
+}
+
+/// Message buffer reading channel
+pub struct MTMessageReader<'a, 'b>
+{
+    b: *mut MTMessageBuffer<'a>,
+    phantom: PhantomData<&'b ()>
+}
+
+unsafe impl Send for MTMessageReader<'_, '_> {}
+
+impl MTMessageReader<'_, '_>
+{
+    /// Gets the length of the next frame.
+    /// * Returns the number of bytes of the frame in `Option`.
+    /// * Gets `None` if there is no available frame.
+    pub fn next_len(&self) -> Option<usize>
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        if b.ring.available() > 0 {
+            let mut prefix = [0; LEN_PREFIX];
+            b.ring.copy_out(b.ring.rd_idx, &mut prefix);
+
+            Some(u16::from_le_bytes(prefix) as usize)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Reads a frame.
+    /// * `frame` - the buffer to store the read frame.
+    /// * Returns the number of read bytes.
+    /// * Blocks if there is no available frame.
+    /// * The rest of the frame is discarded if `frame` is shorter than it.
+    pub fn read(&mut self, frame: &mut [u8]) -> usize
+    {
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        b.ring.wait_available(1);
+
+        let len = self.next_len().bk_unwrap();
+        let n = frame.len().min(len);
+
+        b.ring.skip(LEN_PREFIX);
+        b.ring.get(&mut frame[..n]);
+        b.ring.skip(len - n);
+
+        n
+    }
+}
+
+impl embedded_io::ErrorType for MTMessageReader<'_, '_>
+{
+    type Error = MTFrameError;
+}
+
+impl embedded_io::Read for MTMessageReader<'_, '_>
+{
+    /// Reads one frame into `buf`.
+    /// Gets `Ok(0)` without blocking if `buf` is empty,
+    /// or `Err(MTFrameError::TooShort)` keeping the frame unread if `buf` is shorter than it.
+    /// Zero-length frames are discarded, since `Ok(0)` would mean the end of the stream.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+    {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let b = unsafe { self.b.as_mut().bk_unwrap() };
+
+        loop {
+            b.ring.wait_available(1);
+
+            let len = self.next_len().bk_unwrap();
+
+            if len > buf.len() {
+                return Err(MTFrameError::TooShort);
+            }
+
+            let n = MTMessageReader::read(self, buf);
+
+            if n > 0 {
+                return Ok(n);
+            }
+        }
+    }
+}
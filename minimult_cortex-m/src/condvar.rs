@@ -0,0 +1,251 @@
+use core::marker::PhantomData;
+use core::ptr::null_mut;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::shared::MTSharedGuard;
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+struct MTCondWaiter
+{
+    next: *mut MTCondWaiter,
+    notified: MTEvent
+}
+
+struct MTCondQueue
+{
+    head: *mut MTCondWaiter, // NOTE: in order of arrival
+    tail: *mut MTCondWaiter
+}
+
+impl MTCondQueue
+{
+    fn push(&mut self, node: *mut MTCondWaiter)
+    {
+        if let Some(tail) = unsafe { self.tail.as_mut() } {
+            tail.next = node;
+        }
+        else {
+            self.head = node;
+        }
+
+        self.tail = node;
+    }
+
+    fn pop(&mut self) -> Option<&mut MTCondWaiter>
+    {
+        let node = unsafe { self.head.as_mut()? };
+
+        self.head = node.next;
+        if self.head.is_null() {
+            self.tail = null_mut();
+        }
+
+        node.next = null_mut();
+
+        Some(node)
+    }
+
+    fn remove(&mut self, node: *mut MTCondWaiter)
+    {
+        let mut prev: *mut MTCondWaiter = null_mut();
+        let mut p = self.head;
+
+        while let Some(n) = unsafe { p.as_mut() } {
+            if p == node {
+                if let Some(prev) = unsafe { prev.as_mut() } {
+                    prev.next = n.next;
+                }
+                else {
+                    self.head = n.next;
+                }
+
+                if self.tail == node {
+                    self.tail = prev;
+                }
+
+                n.next = null_mut();
+                break;
+            }
+
+            prev = p;
+            p = n.next;
+        }
+    }
+}
+
+//
+
+/// Condition variable used with `MTSharedLook` or `MTSharedTouch`
+pub struct MTCondvar<'a>
+{
+    queue: MTCondQueue,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl Send for MTCondvar<'_> {}
+unsafe impl Sync for MTCondvar<'_> {}
+
+impl<'a> MTCondvar<'a>
+{
+    pub(crate) fn new() -> MTCondvar<'a> // NOTE: lifetime safety correctness
+    {
+        MTCondvar {
+            queue: MTCondQueue {
+                head: null_mut(),
+                tail: null_mut()
+            },
+            phantom: PhantomData
+        }
+    }
+}
+
+impl MTCondvar<'_>
+{
+    fn queue(&self) -> *mut MTCondQueue
+    {
+        (&self.queue as *const MTCondQueue) as *mut MTCondQueue // NOTE: mutability conversion
+    }
+
+    fn enqueue(&self, node: *mut MTCondWaiter)
+    {
+        let queue = unsafe { self.queue().as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| queue.push(node));
+    }
+
+    fn block(&self, node: *mut MTCondWaiter, tmo: Option<usize>) -> bool
+    {
+        // NOTE: only a waiter already queued when notified is woken up
+
+        let queue = unsafe { self.queue().as_mut().bk_unwrap() };
+        let notified = unsafe { &(*node).notified };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if notified.cnt() != 0 {
+                return true;
+            }
+
+            if let Some(ticks) = tmo {
+                if !Minimult::wait_timeout(notified, MTEventCond::NotEqual(0), from, ticks) {
+                    return cortex_m::interrupt::free(|_| {
+                        if notified.cnt() != 0 {
+                            true
+                        }
+                        else {
+                            queue.remove(node);
+                            false
+                        }
+                    });
+                }
+            }
+            else {
+                Minimult::wait(notified, MTEventCond::NotEqual(0));
+            }
+        }
+    }
+
+    /// Waits for a notification.
+    /// * `guard: A` - `MTSharedLook` or `MTSharedTouch` to be released while waiting.
+    /// * Returns the guard reacquired after notified.
+    /// * Blocks until notified by `notify_one` or `notify_all`.
+    pub fn wait<A>(&self, guard: A) -> A
+    where A: MTSharedGuard
+    {
+        let mut node = MTCondWaiter {
+            next: null_mut(),
+            notified: MTEvent::new(0)
+        };
+        let node: *mut MTCondWaiter = &mut node;
+        self.enqueue(node);
+
+        guard.relock(|| self.block(node, None)).0
+    }
+
+    /// Waits for a notification with a timeout.
+    /// * `guard: A` - `MTSharedLook` or `MTSharedTouch` to be released while waiting.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the guard reacquired after notified.
+    /// * Blocks until notified by `notify_one` or `notify_all`.
+    /// * Returns `Err(MTTimeout(guard))` with the reacquired guard if not notified within the timeout.
+    pub fn wait_timeout<A>(&self, guard: A, ticks: usize) -> Result<A, MTTimeout<A>>
+    where A: MTSharedGuard
+    {
+        let mut node = MTCondWaiter {
+            next: null_mut(),
+            notified: MTEvent::new(0)
+        };
+        let node: *mut MTCondWaiter = &mut node;
+        self.enqueue(node);
+
+        let (guard, notified) = guard.relock(|| self.block(node, Some(ticks)));
+
+        if notified {
+            Ok(guard)
+        }
+        else {
+            Err(MTTimeout(guard))
+        }
+    }
+
+    /// Waits for a notification while a condition holds.
+    /// * `guard: A` - `MTSharedLook` or `MTSharedTouch` to be released while waiting.
+    /// * `condition: F` - closure which returns `true` to keep waiting.
+    /// * Returns the guard reacquired after the condition gets `false`.
+    /// * Blocks while the condition is `true`, checking it each time notified.
+    pub fn wait_while<A, F>(&self, mut guard: A, mut condition: F) -> A
+    where A: MTSharedGuard + core::ops::Deref, F: FnMut(&A::Target) -> bool
+    {
+        while condition(&*guard) {
+            guard = self.wait(guard);
+        }
+
+        guard
+    }
+
+    /// Wakes up one of the waiting tasks.
+    /// * The task which has been waiting the longest is woken up.
+    /// * Nothing happens if no task is waiting.
+    /// * Can be called from interrupt handlers.
+    pub fn notify_one(&self)
+    {
+        let queue = unsafe { self.queue().as_mut().bk_unwrap() };
+
+        let notified = cortex_m::interrupt::free(|_| {
+            if let Some(node) = queue.pop() {
+                node.notified.incr(); // NOTE: node may be gone once out of this critical section
+                true
+            }
+            else {
+                false
+            }
+        });
+
+        if notified {
+            Minimult::dispatch();
+        }
+    }
+
+    /// Wakes up all of the waiting tasks.
+    /// * Nothing happens if no task is waiting.
+    /// * Can be called from interrupt handlers.
+    pub fn notify_all(&self)
+    {
+        let queue = unsafe { self.queue().as_mut().bk_unwrap() };
+
+        let notified = cortex_m::interrupt::free(|_| {
+            let mut notified = false;
+            while let Some(node) = queue.pop() {
+                node.notified.incr(); // NOTE: node may be gone once out of this critical section
+                notified = true;
+            }
+            notified
+        });
+
+        if notified {
+            Minimult::dispatch();
+        }
+    }
+}
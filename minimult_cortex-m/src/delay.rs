@@ -0,0 +1,81 @@
+use crate::minimult::Minimult;
+
+//
+
+fn to_ticks(t: u32, tick_hz: u32, unit_hz: u64) -> usize
+{
+    if t == 0 {
+        return 0;
+    }
+
+    let ticks = (t as u64 * tick_hz as u64).div_ceil(unit_hz);
+
+    // plus one since the current tick has already partially elapsed
+    (ticks + 1).min(usize::MAX as u64) as usize
+}
+
+fn spin(t: u32, core_hz: u32, unit_hz: u64)
+{
+    let mut cycles = (t as u64 * core_hz as u64).div_ceil(unit_hz);
+
+    while cycles > 0 {
+        let c = cycles.min(u32::MAX as u64);
+
+        cortex_m::asm::delay(c as u32);
+        cycles -= c;
+    }
+}
+
+//
+
+/// Delay provider which blocks a current running task instead of spinning
+pub struct MTDelay
+{
+    tick_hz: u32,
+    core_hz: u32
+}
+
+impl MTDelay
+{
+    /// Creates a delay provider.
+    /// * `tick_hz` - frequency of `Minimult::tick` calls in Hz.
+    /// * `core_hz` - frequency of the core clock in Hz, used to spin outside task context.
+    /// * Returns the created delay provider.
+    /// * Spins instead of blocking when no task is running, e.g. before `run` or in interrupt handlers,
+    ///   so that drivers can be initialized with it.
+    pub fn new(tick_hz: u32, core_hz: u32) -> MTDelay
+    {
+        MTDelay {
+            tick_hz,
+            core_hz
+        }
+    }
+
+    fn delay(&self, t: u32, unit_hz: u64)
+    {
+        if Minimult::in_task() {
+            Minimult::delay(to_ticks(t, self.tick_hz, unit_hz));
+        }
+        else {
+            spin(t, self.core_hz, unit_hz);
+        }
+    }
+}
+
+impl embedded_hal::delay::DelayNs for MTDelay
+{
+    fn delay_ns(&mut self, ns: u32)
+    {
+        self.delay(ns, 1_000_000_000);
+    }
+
+    fn delay_us(&mut self, us: u32)
+    {
+        self.delay(us, 1_000_000);
+    }
+
+    fn delay_ms(&mut self, ms: u32)
+    {
+        self.delay(ms, 1_000);
+    }
+}
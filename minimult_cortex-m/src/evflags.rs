@@ -0,0 +1,130 @@
+use core::marker::PhantomData;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Event flag group of 32 bits
+pub struct MTEventFlags<'a>
+{
+    flags: MTEvent,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl Sync for MTEventFlags<'_> {}
+
+impl<'a> MTEventFlags<'a>
+{
+    pub(crate) fn new(init: u32) -> MTEventFlags<'a> // NOTE: lifetime safety correctness
+    {
+        MTEventFlags {
+            flags: MTEvent::new(init as usize),
+            phantom: PhantomData
+        }
+    }
+}
+
+impl MTEventFlags<'_>
+{
+    fn wait_bits(&self, mask: u32, all: bool, auto_clear: bool, tmo: Option<usize>) -> Result<u32, MTTimeout>
+    {
+        let mask = mask as usize;
+        let cond = || if all {MTEventCond::AllBits(mask)} else {MTEventCond::AnyBits(mask)};
+        let clear_bits = if auto_clear {mask} else {0};
+
+        let flags = (&self.flags as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let flags = unsafe { flags.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if let Some(bits) = flags.take_bits_if(&cond(), clear_bits) {
+                return Ok(bits as u32);
+            }
+
+            if let Some(ticks) = tmo {
+                if !Minimult::wait_timeout(flags, cond(), from, ticks) {
+                    return Err(MTTimeout(()));
+                }
+            }
+            else {
+                Minimult::wait(flags, cond());
+            }
+        }
+    }
+
+    /// Gets event flags.
+    /// * Returns the current bits.
+    pub fn get(&self) -> u32
+    {
+        self.flags.cnt() as u32
+    }
+
+    /// Sets event flags.
+    /// * `bits` - bits to be set.
+    /// * Wakes up tasks waiting for the bits.
+    /// * Can be called from interrupt handlers.
+    pub fn set(&self, bits: u32)
+    {
+        let flags = (&self.flags as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let flags = unsafe { flags.as_mut().bk_unwrap() };
+
+        flags.set_bits(bits as usize);
+        Minimult::signal(flags);
+    }
+
+    /// Clears event flags.
+    /// * `bits` - bits to be cleared.
+    /// * Can be called from interrupt handlers.
+    pub fn clear(&self, bits: u32)
+    {
+        let flags = (&self.flags as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let flags = unsafe { flags.as_mut().bk_unwrap() };
+
+        flags.clear_bits(bits as usize);
+    }
+
+    /// Waits for any of event flags.
+    /// * `mask` - bits to be waited for.
+    /// * `auto_clear` - if `true`, clears the `mask` bits when woken up.
+    /// * Returns the bits when woken up, before cleared.
+    /// * Blocks until any of the `mask` bits is set.
+    pub fn wait_any(&self, mask: u32, auto_clear: bool) -> u32
+    {
+        self.wait_bits(mask, false, auto_clear, None).ok().bk_unwrap()
+    }
+
+    /// Waits for all of event flags.
+    /// * `mask` - bits to be waited for.
+    /// * `auto_clear` - if `true`, clears the `mask` bits when woken up.
+    /// * Returns the bits when woken up, before cleared.
+    /// * Blocks until all of the `mask` bits are set.
+    pub fn wait_all(&self, mask: u32, auto_clear: bool) -> u32
+    {
+        self.wait_bits(mask, true, auto_clear, None).ok().bk_unwrap()
+    }
+
+    /// Waits for any of event flags with a timeout.
+    /// * `mask` - bits to be waited for.
+    /// * `auto_clear` - if `true`, clears the `mask` bits when woken up.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the bits when woken up, before cleared.
+    /// * Blocks until any of the `mask` bits is set.
+    /// * Returns `Err(MTTimeout)` if none of the bits is set within the timeout.
+    pub fn wait_any_timeout(&self, mask: u32, auto_clear: bool, ticks: usize) -> Result<u32, MTTimeout>
+    {
+        self.wait_bits(mask, false, auto_clear, Some(ticks))
+    }
+
+    /// Waits for all of event flags with a timeout.
+    /// * `mask` - bits to be waited for.
+    /// * `auto_clear` - if `true`, clears the `mask` bits when woken up.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the bits when woken up, before cleared.
+    /// * Blocks until all of the `mask` bits are set.
+    /// * Returns `Err(MTTimeout)` if not all of the bits are set within the timeout.
+    pub fn wait_all_timeout(&self, mask: u32, auto_clear: bool, ticks: usize) -> Result<u32, MTTimeout>
+    {
+        self.wait_bits(mask, true, auto_clear, Some(ticks))
+    }
+}
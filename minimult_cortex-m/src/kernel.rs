@@ -1,9 +1,10 @@
 use core::mem::{size_of, align_of, transmute};
-use core::ptr::null_mut;
+use core::ptr::{null, null_mut, slice_from_raw_parts};
 
 use crate::{MTTaskId, MTTaskPri};
 use crate::memory::MTRawArray;
 use crate::bheap::MTBHeapDList;
+use crate::minimult::MTNotifyAction;
 use crate::bk_panic;
 use crate::bkptpanic::BKUnwrap;
 
@@ -75,6 +76,52 @@ extern "C" fn minimult_task_switch()
     }
 }
 
+/*
+ * The faulting stack may be overflowing into the guard region,
+ * so disable the MPU before anything is pushed,
+ * and move on to the initial main stack taken from the vector table.
+ */
+#[cfg(all(armv7m, feature = "mem-manage-handler"))]
+core::arch::global_asm!(
+    ".section .text.MemoryManagement,\"ax\",%progbits",
+    ".global MemoryManagement",
+    ".type MemoryManagement,%function",
+    ".thumb_func",
+    "MemoryManagement:",
+    "    ldr     r0, =0xe000ed94",
+    "    mov     r1, #0",
+    "    str     r1, [r0]",
+    "    dsb",
+    "    isb",
+    "    ldr     r0, =0xe000ed08",
+    "    ldr     r0, [r0]",
+    "    ldr     r0, [r0]",
+    "    mov     sp, r0",
+    "    bl      minimult_mem_manage",
+    ".ltorg"
+);
+
+#[cfg(all(armv7m, feature = "mem-manage-handler"))]
+#[no_mangle]
+extern "C" fn minimult_mem_manage() -> !
+{
+    // NOTE: MPU has been disabled and sp moved to the initial main stack
+
+    let scb_ptr = cortex_m::peripheral::SCB::ptr();
+    let (cfsr, mmfar) = unsafe {
+        ((*scb_ptr).cfsr.read(), (*scb_ptr).mmfar.read())
+    };
+
+    if let Some(tm) = mtkernel_get_ref() {
+        if let Some(tid) = tm.tid {
+            assert!(!tm.stack_guard_hit(tid, cfsr, mmfar),
+                    "tid {}: stack overflow", tid);
+        }
+    }
+
+    panic!("MemManage fault: CFSR {:#010x}", cfsr);
+}
+
 fn setup_stack(sp: *mut usize, data: *mut u8, call_once: usize, inf_loop: fn() -> !) -> *mut usize
 {
     let sp = sp as usize;
@@ -139,7 +186,7 @@ pub(crate) fn mtkernel_get_mut() -> Option<&'static mut MTKernel>
 
 //
 
-fn align_down<A>(x: usize) -> usize
+pub(crate) fn align_down<A>(x: usize) -> usize
 {
     let align = align_of::<A>();
     let y = x / align;
@@ -147,6 +194,36 @@ fn align_down<A>(x: usize) -> usize
     y
 }
 
+pub(crate) fn tick_elapsed(tick: usize, from: usize, ticks: usize) -> bool
+{
+    tick.wrapping_sub(from) >= ticks
+}
+
+const STACK_PAINT: usize = 0xCCCC_CCCC;
+const STACK_CANARY: usize = 0xDEAD_BEEF; // NOTE: at the lowest word of a stack
+const STACK_GUARD_SIZE: usize = 32; // NOTE: minimum MPU region size of ARMv7-M
+
+fn stack_guard_base(sp_start: *mut usize) -> usize
+{
+    let x = sp_start as usize + STACK_GUARD_SIZE - 1;
+    x - (x % STACK_GUARD_SIZE)
+}
+
+fn paint_stack(sp_start: *mut usize, sp_end: *mut usize)
+{
+    let mut p = sp_start;
+
+    unsafe {
+        p.write_volatile(STACK_CANARY);
+        p = p.add(1);
+
+        while p < sp_end {
+            p.write_volatile(STACK_PAINT);
+            p = p.add(1);
+        }
+    }
+}
+
 fn inf_loop() -> !
 {
     let tm = mtkernel_get_mut().bk_unwrap();
@@ -161,17 +238,29 @@ fn inf_loop() -> !
 enum MTState
 {
     None,
+    Spawning,
     Ready,
-    Waiting
+    Waiting,
+    Suspended
 }
 
+#[derive(Clone, Copy)]
 pub(crate) enum MTEventCond
 {
     None,
     Equal(usize),
     NotEqual(usize),
     LessThan(usize),
-    GreaterThan(usize)
+    GreaterThan(usize),
+    AnyBits(usize),
+    AllBits(usize)
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct MTEventWait
+{
+    pub(crate) ev: *const MTEvent,
+    pub(crate) cond: MTEventCond
 }
 
 pub(crate) struct MTEvent
@@ -221,6 +310,75 @@ impl MTEvent
         }
     }
 
+    pub(crate) fn decr_ifgt0(&mut self) -> bool
+    {
+        cortex_m::interrupt::free(|_| {
+            if self.ex_cnt > 0 {
+                self.ex_cnt -= 1;
+                true
+            }
+            else {
+                false
+            }
+        })
+    }
+
+    pub(crate) fn incr_iflt(&mut self, bound: usize) -> bool
+    {
+        cortex_m::interrupt::free(|_| {
+            if self.ex_cnt < bound {
+                self.ex_cnt += 1;
+                true
+            }
+            else {
+                false
+            }
+        })
+    }
+
+    pub(crate) fn add(&mut self, n: usize)
+    {
+        cortex_m::interrupt::free(|_| {
+            self.ex_cnt += n;
+        });
+    }
+
+    pub(crate) fn sub(&mut self, n: usize)
+    {
+        cortex_m::interrupt::free(|_| {
+            self.ex_cnt -= n;
+        });
+    }
+
+    pub(crate) fn set_bits(&mut self, bits: usize)
+    {
+        cortex_m::interrupt::free(|_| {
+            self.ex_cnt |= bits;
+        });
+    }
+
+    pub(crate) fn clear_bits(&mut self, bits: usize)
+    {
+        cortex_m::interrupt::free(|_| {
+            self.ex_cnt &= !bits;
+        });
+    }
+
+    pub(crate) fn take_bits_if(&mut self, cond: &MTEventCond, clear_bits: usize) -> Option<usize>
+    {
+        cortex_m::interrupt::free(|_| {
+            let bits = self.ex_cnt;
+
+            if self.cond_matched(cond) {
+                self.ex_cnt &= !clear_bits;
+                Some(bits)
+            }
+            else {
+                None
+            }
+        })
+    }
+
     fn cond_matched(&self, cond: &MTEventCond) -> bool
     {
         match cond {
@@ -239,12 +397,70 @@ impl MTEvent
             MTEventCond::GreaterThan(target) => {
                 self.cnt() > *target
             }
+            MTEventCond::AnyBits(mask) => {
+                (self.cnt() & *mask) != 0
+            }
+            MTEventCond::AllBits(mask) => {
+                (self.cnt() & *mask) == *mask
+            }
         }
     }
 }
 
 //
 
+pub(crate) struct MTMutexCore
+{
+    owner: Option<MTTaskId>,
+    lock_ev: MTEvent,
+    next_held: *mut MTMutexCore
+}
+
+impl MTMutexCore
+{
+    pub(crate) fn new() -> MTMutexCore
+    {
+        MTMutexCore {
+            owner: None,
+            lock_ev: MTEvent::new(0),
+            next_held: null_mut()
+        }
+    }
+
+    pub(crate) fn lock_ev(&self) -> &MTEvent
+    {
+        &self.lock_ev
+    }
+}
+
+//
+
+pub(crate) struct MTCeilingCore
+{
+    ceiling: MTTaskPri,
+    lock_ev: MTEvent,
+    next_held: *mut MTCeilingCore
+}
+
+impl MTCeilingCore
+{
+    pub(crate) fn new(ceiling: MTTaskPri) -> MTCeilingCore
+    {
+        MTCeilingCore {
+            ceiling,
+            lock_ev: MTEvent::new(0),
+            next_held: null_mut()
+        }
+    }
+
+    pub(crate) fn lock_ev(&self) -> &MTEvent
+    {
+        &self.lock_ev
+    }
+}
+
+//
+
 pub(crate) struct MTTask
 {
     sp_start: *mut usize,
@@ -252,10 +468,101 @@ pub(crate) struct MTTask
     //
     sp: *mut usize,
     state: MTState,
+    pri: MTTaskPri,
+    pri_eff: MTTaskPri,
+    pri_ceil: MTTaskPri,
+    held_ceil: *mut MTCeilingCore,
+    held_mtx: *mut MTMutexCore,
+    wait_mtx: *const MTMutexCore,
     wait_ev: *const MTEvent,
     wait_evcond: MTEventCond,
+    wait_evs: *const [MTEventWait],
+    wait_tmo: Option<(usize, usize)>,
+    tmo_next: Option<MTTaskId>,
     //
-    idle_kick_ev: MTEvent
+    idle_kick_ev: MTEvent,
+    notify_ev: MTEvent,
+    notify_val: u32,
+    exit_ev: *mut MTEvent
+}
+
+impl MTTask
+{
+    fn wait_set(&self) -> bool
+    {
+        !self.wait_ev.is_null() || !self.wait_evs.is_null() || self.wait_tmo.is_some()
+    }
+
+    fn wait_clear(&mut self)
+    {
+        self.wait_ev = null_mut();
+        self.wait_evcond = MTEventCond::None;
+        self.wait_evs = slice_from_raw_parts(null(), 0);
+        self.wait_tmo = None;
+    }
+
+    fn wait_matched(&self, tick: usize) -> bool
+    {
+        let ev_matched = if let Some(ev) = unsafe { self.wait_ev.as_ref() } {
+            ev.cond_matched(&self.wait_evcond)
+        }
+        else {
+            false
+        };
+
+        let evs_matched = if let Some(evs) = unsafe { self.wait_evs.as_ref() } {
+            evs.iter().any(|w| unsafe { w.ev.as_ref() }.is_some_and(|ev| ev.cond_matched(&w.cond)))
+        }
+        else {
+            false
+        };
+
+        let tick_matched = if let Some((from, ticks)) = self.wait_tmo {
+            tick_elapsed(tick, from, ticks)
+        }
+        else {
+            false
+        };
+
+        ev_matched || evs_matched || tick_matched
+    }
+}
+
+fn tmo_link(tasks: &MTRawArray<MTTask>, head: &mut Option<MTTaskId>, tid: MTTaskId, tick: usize, from: usize, ticks: usize)
+{
+    // keep timed-waiting tasks sorted by remaining ticks
+
+    let remaining = |from: usize, ticks: usize| ticks.saturating_sub(tick.wrapping_sub(from));
+    let rem = remaining(from, ticks);
+
+    let mut pp = head;
+
+    while let Some(t) = *pp {
+        let (t_from, t_ticks) = tasks.refer(t).wait_tmo.bk_unwrap();
+
+        if remaining(t_from, t_ticks) > rem {
+            break;
+        }
+
+        pp = &mut tasks.refer(t).tmo_next;
+    }
+
+    tasks.refer(tid).tmo_next = *pp;
+    *pp = Some(tid);
+}
+
+fn tmo_unlink(tasks: &MTRawArray<MTTask>, head: &mut Option<MTTaskId>, tid: MTTaskId)
+{
+    let mut pp = head;
+
+    while let Some(t) = *pp {
+        if t == tid {
+            *pp = tasks.refer(t).tmo_next.take();
+            break;
+        }
+
+        pp = &mut tasks.refer(t).tmo_next;
+    }
 }
 
 struct RefFnOnce
@@ -272,9 +579,12 @@ pub(crate) struct MTKernel
     task_tree: MTBHeapDList<MTTaskId, MTTaskPri>,
     //
     is_set: bool,
+    guard_rgn: Option<u32>,
     sp_loops: *mut usize,
     splim_loops: *mut usize,
-    tid: Option<MTTaskId>
+    tid: Option<MTTaskId>,
+    tick: usize,
+    tmo_head: Option<MTTaskId>
 }
 
 impl MTKernel
@@ -290,9 +600,21 @@ impl MTKernel
                     sp_end: null_mut(),
                     sp: null_mut(),
                     state: MTState::None,
+                    pri: 0,
+                    pri_eff: 0,
+                    pri_ceil: MTTaskPri::MAX,
+                    held_ceil: null_mut(),
+                    held_mtx: null_mut(),
+                    wait_mtx: null_mut(),
                     wait_ev: null_mut(),
                     wait_evcond: MTEventCond::None,
-                    idle_kick_ev: MTEvent::new(0)
+                    wait_evs: slice_from_raw_parts(null(), 0),
+                    wait_tmo: None,
+                    tmo_next: None,
+                    idle_kick_ev: MTEvent::new(0),
+                    notify_ev: MTEvent::new(0),
+                    notify_val: 0,
+                    exit_ev: null_mut()
                 }
             );
         }
@@ -301,25 +623,21 @@ impl MTKernel
             tasks,
             task_tree: MTBHeapDList::new(task_tree_array),
             is_set: false,
+            guard_rgn: None,
             sp_loops: null_mut(),
             splim_loops: null_mut(),
-            tid: None
+            tid: None,
+            tick: 0,
+            tmo_head: None
         }
     }
 
-    pub(crate) fn register_once<T>(&mut self, tid: MTTaskId, pri: MTTaskPri, stack: MTRawArray<usize>, t: T)
+    fn setup_task<T>(&mut self, tid: MTTaskId, pri: MTTaskPri, sp_start: *mut usize, sp_end: *mut usize, t: T)
     where T: FnOnce() + Send // NOTE: unsafe lifetime
     {
-        assert!((tid as usize) < self.tasks.len(),
-                "tid {}: out of number of tasks", tid);
-
         let task = self.tasks.refer(tid);
 
-        assert!(task.state == MTState::None,
-                "tid {}: double registration", tid);
-
-        let sp_start = stack.head();
-        let sp_end = stack.tail();
+        paint_stack(sp_start, sp_end);
 
         let sz = size_of::<T>();
         let rfo = unsafe { transmute::<&dyn FnOnce(), RefFnOnce>(&t) };
@@ -339,19 +657,81 @@ impl MTKernel
          */
         let call_once = unsafe { vtbl.add(3).read() };
 
+        core::mem::forget(t); // moved onto the stack
+
         let sp = setup_stack(sp, data, call_once, inf_loop);
 
-        assert!((sp >= sp_start) && (sp <= sp_end),
-                "tid {}: stack shortage");
+        assert!((sp >= self.stack_floor(sp_start)) && (sp <= sp_end),
+                "tid {}: stack shortage", tid);
 
         task.sp_start = sp_start;
         task.sp_end = sp_end;
         task.sp = sp;
-        task.state = MTState::Ready;
+        task.pri = pri;
+        task.pri_eff = pri;
+        task.pri_ceil = MTTaskPri::MAX;
+        task.held_ceil = null_mut();
+        task.held_mtx = null_mut();
+        task.wait_mtx = null_mut();
+        task.wait_clear();
+        task.exit_ev = null_mut();
+    }
+
+    pub(crate) fn register_once<T>(&mut self, tid: MTTaskId, pri: MTTaskPri,
+                                   sp_start: *mut usize, sp_end: *mut usize, exit_ev: *mut MTEvent, t: T)
+    where T: FnOnce() + Send // NOTE: unsafe lifetime
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        let task = self.tasks.refer(tid);
+
+        assert!(task.state == MTState::None,
+                "tid {}: double registration", tid);
+
+        self.setup_task(tid, pri, sp_start, sp_end, t);
+        self.tasks.refer(tid).exit_ev = exit_ev;
+        self.tasks.refer(tid).state = MTState::Ready;
 
         self.task_tree.add_bheap(tid, pri);
     }
 
+    pub(crate) fn none_registered(&self) -> bool
+    {
+        (0..self.tasks.len()).all(|i| self.tasks.refer(i as MTTaskId).state == MTState::None)
+    }
+
+    #[cfg(armv7m)]
+    pub(crate) fn stack_guard(&mut self)
+    {
+        let mpu_ptr = cortex_m::peripheral::MPU::ptr();
+        let scb_ptr = cortex_m::peripheral::SCB::ptr();
+
+        let dregion = unsafe { ((*mpu_ptr)._type.read() >> 8) & 0xff };
+        assert!(dregion > 0,
+                "MPU: not implemented");
+
+        let rgn = dregion - 1; // highest numbered region takes priority when overlapping
+
+        unsafe {
+            (*mpu_ptr).rnr.write(rgn);
+            (*mpu_ptr).rasr.write(0);
+            (*scb_ptr).shcsr.modify(|r| r | (1 << 16)); // MEMFAULTENA
+            (*mpu_ptr).ctrl.modify(|r| r | (1 << 2) | (1 << 0)); // PRIVDEFENA, ENABLE
+        }
+
+        cortex_m::asm::dsb();
+        cortex_m::asm::isb();
+
+        self.guard_rgn = Some(rgn);
+    }
+
+    #[cfg(not(armv7m))]
+    pub(crate) fn stack_guard(&mut self)
+    {
+        // NOTE: no MPU guard region; MSPLIM or a canary checks instead
+    }
+
     pub(crate) fn run(&mut self) -> !
     {
         let control = cortex_m::register::control::read();
@@ -372,6 +752,216 @@ impl MTKernel
         }
     }
 
+    // ----- ----- Task context ----- ----- //
+
+    pub(crate) fn spawn_once<T>(&mut self, tid: Option<MTTaskId>, pri: MTTaskPri,
+                                sp_start: *mut usize, sp_end: *mut usize, exit_ev: *mut MTEvent, t: T) -> Option<MTTaskId>
+    where T: FnOnce() + Send // NOTE: unsafe lifetime
+    {
+        if let Some(tid) = tid {
+            assert!((tid as usize) < self.tasks.len(),
+                    "tid {}: out of number of tasks", tid);
+        }
+
+        // claim a slot and the stack, then set up the stack outside the critical section
+
+        let tid = cortex_m::interrupt::free(|_| {
+            let mut free_tid = None;
+
+            for i in 0..self.tasks.len() {
+                let task = self.tasks.refer(i);
+
+                if task.state != MTState::None {
+                    if task.sp_start == sp_start {
+                        return None; // stack in use
+                    }
+                }
+                else if free_tid.is_none() && (tid.is_none() || tid == Some(i as MTTaskId)) {
+                    free_tid = Some(i as MTTaskId);
+                }
+            }
+
+            let tid = free_tid?;
+            let task = self.tasks.refer(tid);
+
+            task.state = MTState::Spawning;
+            task.sp_start = sp_start;
+            task.wait_mtx = null_mut();
+
+            Some(tid)
+        })?;
+
+        self.setup_task(tid, pri, sp_start, sp_end, t);
+
+        cortex_m::interrupt::free(|_| {
+            let task = self.tasks.refer(tid);
+
+            task.exit_ev = exit_ev;
+            task.state = MTState::Ready;
+
+            self.task_tree.add_flist(tid, pri); // NOTE: moved to bheap when task-switching
+        });
+
+        self.dispatch();
+
+        Some(tid)
+    }
+
+    pub(crate) fn stack_in_use(&self, sp_start: *mut usize) -> bool
+    {
+        for i in 0..self.tasks.len() {
+            let task = self.tasks.refer(i);
+
+            if (task.state != MTState::None) && (task.sp_start == sp_start) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub(crate) fn mutex_lock(&mut self, mtx: &mut MTMutexCore, block: bool) -> bool
+    {
+        let tid = self.tid.bk_unwrap();
+
+        assert!(mtx.owner != Some(tid),
+                "tid {}: recursive mutex lock", tid);
+
+        cortex_m::interrupt::free(|_| {
+            let task = self.tasks.refer(tid);
+
+            if mtx.owner.is_none() {
+                mtx.owner = Some(tid);
+                mtx.lock_ev.incr();
+
+                mtx.next_held = task.held_mtx;
+                task.held_mtx = mtx;
+                task.wait_mtx = null_mut();
+
+                self.update_pri(tid); // inherit from the remaining waiters
+
+                true
+            }
+            else {
+                if block {
+                    task.wait_mtx = mtx;
+
+                    self.inherit_pri(mtx);
+                }
+
+                false
+            }
+        })
+    }
+
+    pub(crate) fn mutex_cancel(&mut self, mtx: &MTMutexCore)
+    {
+        let tid = self.tid.bk_unwrap();
+
+        cortex_m::interrupt::free(|_| {
+            self.tasks.refer(tid).wait_mtx = null();
+
+            self.inherit_pri(mtx); // back from the priority inherited from this task
+        });
+    }
+
+    pub(crate) fn mutex_unlock(&mut self, mtx: &mut MTMutexCore)
+    {
+        let tid = mtx.owner.bk_unwrap();
+
+        cortex_m::interrupt::free(|_| {
+            let task = self.tasks.refer(tid);
+
+            let target: *mut MTMutexCore = mtx;
+            let mut pp: *mut *mut MTMutexCore = &mut task.held_mtx;
+
+            unsafe {
+                while !(*pp).is_null() {
+                    if *pp == target {
+                        *pp = mtx.next_held;
+                        break;
+                    }
+                    pp = &mut (**pp).next_held;
+                }
+            }
+
+            mtx.next_held = null_mut();
+            mtx.owner = None;
+            mtx.lock_ev.decr();
+
+            self.update_pri(tid); // back from the inherited priority
+        });
+
+        self.signal(&mtx.lock_ev);
+    }
+
+    pub(crate) fn ceiling_lock(&mut self, ceil: &mut MTCeilingCore) -> bool
+    {
+        let tid = self.tid.bk_unwrap();
+
+        assert!(self.tasks.refer(tid).pri >= ceil.ceiling,
+                "tid {}: priority higher than ceiling", tid);
+
+        cortex_m::interrupt::free(|_| {
+            if ceil.lock_ev.cnt() != 0 {
+                return false;
+            }
+
+            ceil.lock_ev.incr();
+
+            let task = self.tasks.refer(tid);
+
+            ceil.next_held = task.held_ceil;
+            task.held_ceil = ceil;
+            task.pri_ceil = task.pri_ceil.min(ceil.ceiling);
+
+            self.update_pri(tid); // immediately up to the ceiling
+
+            true
+        })
+    }
+
+    pub(crate) fn ceiling_unlock(&mut self, ceil: &mut MTCeilingCore)
+    {
+        let tid = self.tid.bk_unwrap();
+
+        cortex_m::interrupt::free(|_| {
+            let task = self.tasks.refer(tid);
+
+            let target: *mut MTCeilingCore = ceil;
+            let mut pp: *mut *mut MTCeilingCore = &mut task.held_ceil;
+
+            unsafe {
+                while !(*pp).is_null() {
+                    if *pp == target {
+                        *pp = ceil.next_held;
+                        break;
+                    }
+                    pp = &mut (**pp).next_held;
+                }
+            }
+
+            ceil.next_held = null_mut();
+            ceil.lock_ev.decr();
+
+            // the highest ceiling of the remaining, in whatever order unlocked
+
+            let mut pri_ceil = MTTaskPri::MAX;
+            let mut c = task.held_ceil as *const MTCeilingCore;
+
+            while let Some(r) = unsafe { c.as_ref() } {
+                pri_ceil = pri_ceil.min(r.ceiling);
+                c = r.next_held;
+            }
+
+            task.pri_ceil = pri_ceil;
+
+            self.update_pri(tid); // back from the ceiling
+        });
+
+        self.signal(&ceil.lock_ev);
+    }
+
     // ----- ----- Interrupt context ----- ----- //
 
     fn save_sp(&mut self, curr_sp: *mut usize, curr_splim: *mut usize) -> (*mut usize, *mut usize)
@@ -398,32 +988,49 @@ impl MTKernel
 
         cortex_m::peripheral::SCB::clear_pendsv();
 
+        // release guard region so that the canary can be read
+
+        self.set_stack_guard(None);
+
+        // check canary
+
+        if let Some(tid) = self.tid {
+            let canary = unsafe { self.tasks.refer(tid).sp_start.read_volatile() };
+
+            assert!(canary == STACK_CANARY,
+                    "tid {}: stack overflow", tid);
+        }
+
         // change state
 
-        if let Some(task) = self.task_current() {
-            match task.state {
+        if let Some(tid) = self.tid {
+            match self.tasks.refer(tid).state {
                 MTState::None => {
-                    self.task_tree.remove_bheap_h();
+                    self.task_tree.remove_bheap(tid);
                 }
-                MTState::Waiting => {
-                    self.task_tree.bheap_h_to_flist_h();
+                MTState::Waiting | MTState::Suspended => {
+                    self.task_tree.bheap_to_flist_h(tid);
                 }
                 _  => {}
             }
         }
 
-        // scan to check if Idle/Wait to Ready
+        // scan to check if Idle/Wait/Delay to Ready, or newly spawned/resumed
 
         let tasks = &self.tasks;
+        let tmo_head = &mut self.tmo_head;
+        let tick = self.tick;
 
         self.task_tree.flist_scan(|tid| {
             let task = tasks.refer(tid);
 
             match task.state {
                 MTState::Waiting => {
-                    let ev = unsafe { task.wait_ev.as_ref().bk_unwrap() };
-                    
-                    if ev.cond_matched(&task.wait_evcond) {
+                    if task.wait_matched(tick) {
+                        if task.wait_tmo.is_some() {
+                            tmo_unlink(tasks, tmo_head, tid);
+                        }
+                        task.wait_clear();
                         task.state = MTState::Ready;
                         true
                     }
@@ -431,21 +1038,38 @@ impl MTKernel
                         false
                     }
                 }
+                MTState::Ready => {
+                    true // spawned or resumed
+                }
+                MTState::Suspended => {
+                    false
+                }
                 _ => bk_panic!()
             }
         });
 
         // round robin
 
-        if let Some(task) = self.task_current() {
-            match task.state {
+        if let Some(tid) = self.tid {
+            match self.tasks.refer(tid).state {
                 MTState::Ready => {
-                    self.task_tree.round_bheap_h();
+                    self.task_tree.round_bheap(tid);
                 }
                 _  => {}
             }
         }
 
+        // move out tasks no longer Ready, e.g. suspended, lazily when they come to head
+
+        while let Some(tid) = self.task_tree.bheap_h() {
+            if self.tasks.refer(tid).state != MTState::Ready {
+                self.task_tree.bheap_h_to_flist_h();
+            }
+            else {
+                break;
+            }
+        }
+
         // find highest priority Ready task
 
         let (next_tid, next_sp, next_splim) = if let Some(tid) = self.task_tree.bheap_h() {
@@ -457,46 +1081,206 @@ impl MTKernel
 
         self.tid = next_tid;
 
+        // guard below the next task stack
+
+        if let Some(tid) = next_tid {
+            self.set_stack_guard(Some(self.tasks.refer(tid).sp_start));
+        }
+
         (next_sp, next_splim)
     }
 
+    #[cfg(armv7m)]
+    fn set_stack_guard(&self, sp_start: Option<*mut usize>)
+    {
+        if let Some(rgn) = self.guard_rgn {
+            let mpu_ptr = cortex_m::peripheral::MPU::ptr();
+
+            unsafe {
+                (*mpu_ptr).rnr.write(rgn);
+
+                if let Some(sp_start) = sp_start {
+                    (*mpu_ptr).rbar.write(stack_guard_base(sp_start) as u32);
+                    (*mpu_ptr).rasr.write((1 << 28) | (4 << 1) | (1 << 0)); // XN, AP: no access, SIZE: 32 bytes, ENABLE
+                }
+                else {
+                    (*mpu_ptr).rasr.write(0);
+                }
+            }
+
+            cortex_m::asm::dsb();
+            cortex_m::asm::isb();
+        }
+    }
+
+    #[cfg(not(armv7m))]
+    fn set_stack_guard(&self, _sp_start: Option<*mut usize>)
+    {
+    }
+
+    #[cfg(all(armv7m, feature = "mem-manage-handler"))]
+    fn stack_guard_hit(&self, tid: MTTaskId, cfsr: u32, mmfar: u32) -> bool
+    {
+        if self.guard_rgn.is_none() {
+            return false;
+        }
+
+        if cfsr & (1 << 4) != 0 { // MSTKERR
+            return true;
+        }
+
+        if cfsr & ((1 << 7) | (1 << 1)) == ((1 << 7) | (1 << 1)) { // MMARVALID, DACCVIOL
+            let base = stack_guard_base(self.tasks.refer(tid).sp_start);
+            let addr = mmfar as usize;
+
+            return (addr >= base) && (addr < base + STACK_GUARD_SIZE);
+        }
+
+        false
+    }
+
     // ----- ----- Task context ----- ----- //
 
     fn none(&mut self)
     {
         let task = self.task_current().bk_unwrap();
 
-        task.state = MTState::None; // NOTE: atomic access might be necessary
+        cortex_m::interrupt::free(|_| {
+            task.state = MTState::None;
+
+            if let Some(ev) = unsafe { task.exit_ev.as_mut() } {
+                ev.incr(); // atomically with None so that a joining task never sees it running
+            }
+        });
         
         self.dispatch();
     }
 
-    pub(crate) fn idle(&mut self)
+    pub(crate) fn idle(&mut self, tmo: Option<(usize, usize)>) -> bool
     {
         loop {
+            let tick = self.tick_count();
             let task = self.task_current().bk_unwrap();
 
             if task.idle_kick_ev.cnt() != 0 {
                 task.idle_kick_ev.decr();
-                break;
+                return true;
+            }
+
+            if let Some((from, ticks)) = tmo {
+                if tick_elapsed(tick, from, ticks) {
+                    return false;
+                }
             }
 
             task.wait_ev = &task.idle_kick_ev;
             task.wait_evcond = MTEventCond::NotEqual(0);
-            task.state = MTState::Waiting; // NOTE: atomic access might be necessary
-            
-            self.dispatch();
+            task.wait_tmo = tmo;
+
+            self.wait_start();
         }
     }
 
-    pub(crate) fn wait(&mut self, ev: &MTEvent, evcond: MTEventCond)
+    pub(crate) fn wait_notify(&mut self, tmo: Option<(usize, usize)>) -> Option<u32>
+    {
+        loop {
+            let tick = self.tick_count();
+            let task = self.task_current().bk_unwrap();
+
+            if task.notify_ev.cnt() != 0 {
+                let val = cortex_m::interrupt::free(|_| {
+                    task.notify_ev.decr();
+                    core::mem::replace(&mut task.notify_val, 0)
+                });
+                return Some(val);
+            }
+
+            if let Some((from, ticks)) = tmo {
+                if tick_elapsed(tick, from, ticks) {
+                    return None;
+                }
+            }
+
+            task.wait_ev = &task.notify_ev;
+            task.wait_evcond = MTEventCond::NotEqual(0);
+            task.wait_tmo = tmo;
+
+            self.wait_start();
+        }
+    }
+
+    pub(crate) fn wait(&mut self, ev: &MTEvent, evcond: MTEventCond, tmo: Option<(usize, usize)>)
     {
         let task = self.task_current().bk_unwrap();
 
         task.wait_ev = ev;
         task.wait_evcond = evcond;
-        task.state = MTState::Waiting; // NOTE: atomic access might be necessary
-        
+        task.wait_tmo = tmo;
+
+        self.wait_start();
+    }
+
+    pub(crate) fn select(&mut self, evs: &[MTEventWait], kick: bool, tmo: Option<(usize, usize)>) -> Option<Option<usize>>
+    {
+        loop {
+            let tick = self.tick_count();
+            let task = self.task_current().bk_unwrap();
+
+            for (i, w) in evs.iter().enumerate() {
+                if unsafe { w.ev.as_ref() }.bk_unwrap().cond_matched(&w.cond) {
+                    return Some(Some(i));
+                }
+            }
+
+            if kick && (task.idle_kick_ev.cnt() != 0) {
+                task.idle_kick_ev.decr();
+                return Some(None);
+            }
+
+            if let Some((from, ticks)) = tmo {
+                if tick_elapsed(tick, from, ticks) {
+                    return None;
+                }
+            }
+
+            if kick {
+                task.wait_ev = &task.idle_kick_ev;
+                task.wait_evcond = MTEventCond::NotEqual(0);
+            }
+            task.wait_evs = evs;
+            task.wait_tmo = tmo;
+
+            self.wait_start();
+        }
+    }
+
+    pub(crate) fn delay(&mut self, from: usize, ticks: usize)
+    {
+        let task = self.task_current().bk_unwrap();
+
+        task.wait_ev = null_mut();
+        task.wait_evcond = MTEventCond::None;
+        task.wait_tmo = Some((from, ticks));
+
+        self.wait_start();
+    }
+
+    fn wait_start(&mut self)
+    {
+        let tid = self.tid.bk_unwrap();
+
+        cortex_m::interrupt::free(|_| {
+            if let Some((from, ticks)) = self.tasks.refer(tid).wait_tmo {
+                tmo_link(&self.tasks, &mut self.tmo_head, tid, self.tick, from, ticks);
+            }
+
+            let task = self.tasks.refer(tid);
+
+            if task.state == MTState::Ready {
+                task.state = MTState::Waiting;
+            } // NOTE: if suspended meanwhile, kept and resumed to Waiting
+        });
+
         self.dispatch();
     }
 
@@ -507,6 +1291,60 @@ impl MTKernel
 
     // ----- ----- Task and Interrupt context ----- ----- //
 
+    fn update_pri(&mut self, tid: MTTaskId) -> bool
+    {
+        // effective priority: the highest of its own, the ceilings and the waiters of the held mutexes
+
+        let mut pri = self.tasks.refer(tid).pri.min(self.tasks.refer(tid).pri_ceil);
+
+        let mut mtx = self.tasks.refer(tid).held_mtx as *const MTMutexCore;
+
+        while let Some(m) = unsafe { mtx.as_ref() } {
+            for i in 0..self.tasks.len() {
+                let waiter = self.tasks.refer(i);
+
+                if (waiter.state != MTState::None) && (waiter.wait_mtx == mtx) {
+                    pri = pri.min(waiter.pri_eff);
+                }
+            }
+
+            mtx = m.next_held;
+        }
+
+        let task = self.tasks.refer(tid);
+
+        if task.pri_eff != pri {
+            task.pri_eff = pri;
+            self.task_tree.set_key(tid, pri);
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn inherit_pri(&mut self, mtx: *const MTMutexCore)
+    {
+        // follow the chain of owners, bounded in case of deadlock
+
+        let mut mtx = mtx;
+
+        for _ in 0..self.tasks.len() {
+            let owner = if let Some(owner) = unsafe { mtx.as_ref() }.and_then(|m| m.owner) {
+                owner
+            }
+            else {
+                break;
+            };
+
+            if !self.update_pri(owner) {
+                break;
+            }
+
+            mtx = self.tasks.refer(owner).wait_mtx;
+        }
+    }
+
     fn task_current(&mut self) -> Option<&mut MTTask>
     {
         if let Some(curr_tid) = self.tid {
@@ -536,8 +1374,177 @@ impl MTKernel
         self.dispatch(); // NOTE: room of optimization using ev
     }
 
+    pub(crate) fn notify(&mut self, tid: MTTaskId, value: u32, action: MTNotifyAction) -> bool
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        let task = self.tasks.refer(tid);
+
+        let notified = cortex_m::interrupt::free(|_| {
+            let pending = task.notify_ev.cnt() != 0;
+
+            match action {
+                MTNotifyAction::SetBits => task.notify_val |= value,
+                MTNotifyAction::Increment => task.notify_val = task.notify_val.wrapping_add(1),
+                MTNotifyAction::Overwrite => task.notify_val = value,
+                MTNotifyAction::OverwriteIfEmpty => {
+                    if pending {
+                        return false;
+                    }
+                    task.notify_val = value;
+                }
+            }
+
+            if !pending {
+                task.notify_ev.incr();
+            }
+
+            true
+        });
+
+        self.dispatch(); // NOTE: room of optimization using ev
+
+        notified
+    }
+
+    fn stack_floor(&self, sp_start: *mut usize) -> *mut usize
+    {
+        // lowest free word above a canary, or above a guard region
+
+        if self.guard_rgn.is_some() {
+            (stack_guard_base(sp_start) + STACK_GUARD_SIZE) as *mut usize
+        }
+        else {
+            unsafe { sp_start.add(1) }
+        }
+    }
+
+    pub(crate) fn stack_high_water(&self, tid: MTTaskId) -> usize
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        let task = self.tasks.refer(tid);
+
+        if task.sp_start.is_null() {
+            return 0;
+        }
+
+        let mut free = 0;
+        let mut p = self.stack_floor(task.sp_start);
+
+        while (p < task.sp_end) && (unsafe { p.read_volatile() } == STACK_PAINT) {
+            free += 1;
+            p = unsafe { p.add(1) };
+        }
+
+        free
+    }
+
+    pub(crate) fn suspend(&mut self, tid: MTTaskId)
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        let task = self.tasks.refer(tid);
+
+        cortex_m::interrupt::free(|_| {
+            match task.state {
+                MTState::Ready | MTState::Waiting => {
+                    task.state = MTState::Suspended; // NOTE: waiting condition is kept
+                }
+                _ => {}
+            }
+        });
+
+        self.dispatch();
+    }
+
+    pub(crate) fn resume(&mut self, tid: MTTaskId)
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        let task = self.tasks.refer(tid);
+
+        cortex_m::interrupt::free(|_| {
+            if task.state == MTState::Suspended {
+                task.state = if task.wait_set() {MTState::Waiting} else {MTState::Ready};
+            }
+        });
+
+        self.dispatch();
+    }
+
+    pub(crate) fn set_priority(&mut self, tid: MTTaskId, pri: MTTaskPri)
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        let outranked = cortex_m::interrupt::free(|_| {
+            let task = self.tasks.refer(tid);
+
+            task.pri = pri;
+
+            if (task.state == MTState::None) || (task.state == MTState::Spawning) {
+                return false;
+            }
+
+            let wait_mtx = task.wait_mtx;
+
+            if self.update_pri(tid) {
+                self.inherit_pri(wait_mtx);
+            }
+
+            self.task_tree.bheap_h() != self.tid
+        });
+
+        if outranked {
+            self.dispatch();
+        }
+    }
+
+    pub(crate) fn get_priority(&self, tid: MTTaskId) -> MTTaskPri
+    {
+        assert!((tid as usize) < self.tasks.len(),
+                "tid {}: out of number of tasks", tid);
+
+        self.tasks.refer(tid).pri
+    }
+
     pub(crate) fn curr_tid(&self) -> Option<MTTaskId>
     {
         self.tid
     }
+
+    pub(crate) fn tick(&mut self)
+    {
+        self.tick = self.tick.wrapping_add(1); // NOTE: only an interrupt is supposed to increment
+
+        // dispatch only when the earliest deadlines expire
+
+        let mut expired = false;
+
+        while let Some(tid) = self.tmo_head {
+            let task = self.tasks.refer(tid);
+            let (from, ticks) = task.wait_tmo.bk_unwrap();
+
+            if !tick_elapsed(self.tick, from, ticks) {
+                break;
+            }
+
+            self.tmo_head = task.tmo_next.take();
+            expired = true;
+        }
+
+        if expired {
+            self.dispatch();
+        }
+    }
+
+    pub(crate) fn tick_count(&self) -> usize
+    {
+        unsafe { core::ptr::read_volatile(&self.tick) }
+    }
 }
@@ -16,20 +16,71 @@ This crate for Rust provides a minimal multitask library `Minimult` for Cortex-M
 * Task like that of a typical RTOS
   * `Minimult` can take closures and register them as tasks.
   * `Minimult` runs into a loop to start dispatching those tasks.
-    * *Not supported: dynamically creating and spawning.*
+  * `MTStack` can spawn a task dynamically from a running task.
+    * A stack is reserved in advance and reused after the spawned task finishes.
+    * `MTJoinHandle` joins the spawned task and takes its return value.
+  * `register_join`, `join` and `try_join`
+    * A registered task also returns a value, taken by joining it.
+  * `suspend` and `resume`
+    * A task is frozen by other tasks/interrupts without losing its wake-up.
+  * `stack_high_water`
+    * Stacks are painted so that the minimum free words are reported, and a canary is checked at every task switch.
+  * `stack_guard`
+    * An MPU guard region at the bottom of the running task stack turns an overflow into a reported panic on Armv7-M,
+      at the cost of up to 32 bytes plus alignment loss of each stack.
+    * The `MemoryManagement` handler reporting it is opted in by `mem-manage-handler` feature.
 * Synchronization
   * `idle` and `kick`
     * A task goes into an idle state and other tasks/interrupts wake it up by kicking.
+  * `notify` and `wait_notify`
+    * Other tasks/interrupts hand a 32-bit value to a task directly, by setting bits, incrementing or overwriting.
   * `MTMsgSender` and `MTMsgReceiver`
     * Task-to-task communication by message passing.
+    * The sender can be cloned for multiple producer tasks.
+    * `try_*` and `send_from_isr` never block, usable also from interrupts.
+  * `MTPrioSender` and `MTPrioReceiver`
+    * Message passing where a message with the most urgent key is received first.
+  * `MTRendezvousSender` and `MTRendezvousReceiver`
+    * Message passing without a buffer, where a sender is blocked until a receiver takes the message.
+  * `MTBroadcastSender` and `MTBroadcastReceiver`
+    * One-to-many communication where each subscriber receives every message or is told it lagged, never blocking the sender.
+  * `MTStreamBuffer` and `MTMessageBuffer`
+    * Byte streams and variable-length frames, implementing `embedded-io` `Read` and `Write`.
   * `MTSharedCh`
     * Shared variable among tasks.
+  * `MTWatchSender` and `MTWatchReceiver`
+    * Latest value overwritten without blocking, and received when changed.
+  * `MTCondvar`
+    * Condition variable to wait for a change of a shared variable without polling.
+  * `MTMutexCh`
+    * Mutex with priority inheritance to avoid priority inversion.
+  * `MTResourceCh`
+    * Resource with the immediate priority ceiling protocol for bounded blocking.
+  * `MTSemaphore`
+    * Counting and binary semaphore, released also from interrupts.
+  * `MTEventFlags`
+    * Group of 32 event flags waited for any or all, set also from interrupts.
+  * `MTBarrier`
+    * Barrier to release a number of tasks together after all of them arrive.
+  * `MTSelect`
+    * A task waits for any of message receivers, shared variables and a kick, knowing which one is ready.
+* Time management
+  * `tick` and `delay`
+    * A task is delayed for a number of ticks, or periodically with `delay_until`.
+  * `*_timeout` variants
+    * Blocking operations give up with `MTTimeout` after a number of ticks.
+  * `MTDelay`
+    * `embedded-hal` delay which blocks a task instead of spinning, and spins only outside tasks.
+  * `MTTimer`
+    * One-shot and periodic software timers calling back closures in a timer service task.
 * Priority-based dispatching
   * A higher priority task preempts lower priority tasks.
   * Round-robin dispatching within the same priority tasks.
   * `dispatch` can be directly requested so that timer-based preemption is also possible.
+  * `set_priority` changes a task priority at runtime.
 * Static memory allocation
   * `Minimult` doesn't require a global allocator but reserves a bunch of memory block in advance.
+  * `MTPool` hands out fixed-size blocks as `MTPoolBox`, filled in place by `alloc_with` and passed by message queues without copying.
 
 # Examples
 ## Usage
@@ -62,9 +113,11 @@ fn main() -> !
     let shch1 = sh.ch();
     let shch2 = sh.ch();
 
+    let cv = mt.condvar();
+
     mt.register(0/*tid*/, 1, 256, || task0(snd));
-    mt.register(1/*tid*/, 1, 256, || task1(rcv, shch1));
-    mt.register(2/*tid*/, 1, 256, || task2(shch2));
+    mt.register(1/*tid*/, 1, 256, || task1(rcv, shch1, &cv));
+    mt.register(2/*tid*/, 1, 256, || task2(shch2, &cv));
 
     // SysTick settings
     let cmperi = Peripherals::take().unwrap();
@@ -83,6 +136,7 @@ fn main() -> !
     //drop(sh);
     //drop(shch1);
     //drop(shch2);
+    //drop(cv);
     
     hprintln!("Minimult run").unwrap();
     mt.run()
@@ -104,7 +158,7 @@ fn task0(mut snd: MTMsgSender<u32>)
     }
 }
 
-fn task1(mut rcv: MTMsgReceiver<u32>, shch: MTSharedCh<u32>)
+fn task1(mut rcv: MTMsgReceiver<u32>, shch: MTSharedCh<u32>, cv: &MTCondvar)
 {
     for i in 0.. {
         let vrcv = rcv.receive();
@@ -113,18 +167,21 @@ fn task1(mut rcv: MTMsgReceiver<u32>, shch: MTSharedCh<u32>)
         hprintln!("task1 touch {}", vrcv).unwrap();
         let mut vtouch = shch.touch();
         *vtouch = vrcv;
+        drop(vtouch);
+
+        cv.notify_all();
     }
 }
 
-fn task2(shch: MTSharedCh<u32>)
+fn task2(shch: MTSharedCh<u32>, cv: &MTCondvar)
 {
     let mut j = 0;
 
     while j < 50 {
-        let vlook = shch.look();
+        let vlook = cv.wait_while(shch.look(), |v| *v == j);
 
-        assert!(j <= *vlook);
-        //hprintln!("task2 look {}", *vlook).unwrap(); // many lines printed
+        assert!(j < *vlook);
+        hprintln!("task2 look {}", *vlook).unwrap();
         j = *vlook;
     }
 
@@ -157,8 +214,24 @@ mod minimult;  // Lifetime safe and high-level API wrapper
 mod kernel;    // Low-level unsafe and lifetime unbounded singleton
 mod bheap;     // binary heap and list
 mod memory;    // static memory allocation
+mod pool;      // fixed-size block pool
 mod msgqueue;  // message queue
+mod prioqueue; // priority message queue
+mod rendezvous; // rendezvous channel
+mod broadcast; // broadcast channel
+mod bytebuf;   // stream and message buffers of bytes
 mod shared;    // read-write shared variable
+mod watch;     // latest-value watch channel
+mod mutex;     // mutex with priority inheritance
+mod resource;  // resource with priority ceiling
+mod semaphore; // counting semaphore
+mod evflags;   // event flag group
+mod condvar;   // condition variable
+mod barrier;   // barrier
+mod select;    // select over multiple sources
+mod delay;     // embedded-hal delay
+mod timer;     // software timer
+mod task;      // dynamic task spawning
 mod bkptpanic; // bkpt panic, assert and unwrap
 
 /// Task identifier
@@ -168,19 +241,93 @@ pub type MTTaskId = u16;
 pub type MTTaskPri = u8;
 
 pub use crate::minimult::{
-    Minimult
+    Minimult,
+    MTTimeout, MTNotifyAction
 };
 
 pub use crate::memory::{
     MTMemBlk
 };
 
+pub use crate::pool::{
+    MTPool, MTPoolBox
+};
+
 pub use crate::msgqueue::{
     MTMsgSender, MTMsgReceiver,
     MTMsgQueue
 };
 
+pub use crate::prioqueue::{
+    MTPrioSender, MTPrioReceiver,
+    MTPrioQueue
+};
+
+pub use crate::rendezvous::{
+    MTRendezvousSender, MTRendezvousReceiver,
+    MTRendezvous
+};
+
+pub use crate::broadcast::{
+    MTBroadcastSender, MTBroadcastReceiver,
+    MTBroadcast, MTLagged
+};
+
+pub use crate::bytebuf::{
+    MTStreamWriter, MTStreamReader,
+    MTStreamBuffer,
+    MTMessageWriter, MTMessageReader,
+    MTMessageBuffer, MTFrameError
+};
+
 pub use crate::shared::{
     MTSharedCh,
-    MTShared, MTSharedLook, MTSharedTouch
+    MTShared, MTSharedLook, MTSharedTouch, MTSharedGuard
+};
+
+pub use crate::watch::{
+    MTWatchSender, MTWatchReceiver,
+    MTWatch
+};
+
+pub use crate::mutex::{
+    MTMutexCh,
+    MTMutex, MTMutexGuard
+};
+
+pub use crate::resource::{
+    MTResourceCh,
+    MTResource, MTResourceGuard
+};
+
+pub use crate::semaphore::{
+    MTSemaphore
+};
+
+pub use crate::evflags::{
+    MTEventFlags
+};
+
+pub use crate::condvar::{
+    MTCondvar
+};
+
+pub use crate::barrier::{
+    MTBarrier
+};
+
+pub use crate::select::{
+    MTSelect, MTSelected
+};
+
+pub use crate::delay::{
+    MTDelay
+};
+
+pub use crate::timer::{
+    MTTimer
+};
+
+pub use crate::task::{
+    MTStack, MTJoinHandle
 };
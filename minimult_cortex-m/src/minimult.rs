@@ -1,10 +1,45 @@
+use core::ptr::null_mut;
+
 use crate::{MTTaskId, MTTaskPri};
 use crate::msgqueue::MTMsgQueue;
+use crate::broadcast::MTBroadcast;
+use crate::rendezvous::MTRendezvous;
+use crate::prioqueue::MTPrioQueue;
+use crate::bytebuf::{MTStreamBuffer, MTMessageBuffer};
+use crate::pool::MTPool;
 use crate::shared::MTShared;
+use crate::watch::MTWatch;
+use crate::mutex::MTMutex;
+use crate::resource::MTResource;
+use crate::semaphore::MTSemaphore;
+use crate::evflags::MTEventFlags;
+use crate::condvar::MTCondvar;
+use crate::barrier::MTBarrier;
+use crate::task::{MTStack, MTJoinHandle};
+use crate::timer::{MTTimer, mttimersvc_create, mttimersvc_exists, mttimersvc_loop};
 use crate::memory::{MTMemBlk, MTAlloc};
-use crate::kernel::{mtkernel_create, mtkernel_get_ref, mtkernel_get_mut, MTEvent, MTEventCond};
+use crate::kernel::{mtkernel_create, mtkernel_get_ref, mtkernel_get_mut, MTEvent, MTEventCond, MTEventWait, MTMutexCore, MTCeilingCore, tick_elapsed};
 use crate::bkptpanic::BKUnwrap;
 
+/// Error of a blocking operation which has timed out
+/// * `V` - value given back to a caller, e.g. a message not sent.
+#[derive(Debug)]
+pub struct MTTimeout<V = ()>(pub V);
+
+/// Action of `Minimult::notify` on a notification value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MTNotifyAction
+{
+    /// Sets the bits of a given value.
+    SetBits,
+    /// Increments by one, ignoring a given value.
+    Increment,
+    /// Overwrites with a given value.
+    Overwrite,
+    /// Overwrites with a given value only if no notification is pending.
+    OverwriteIfEmpty
+}
+
 /// Multitasking API
 pub struct Minimult<'a>
 {
@@ -27,7 +62,7 @@ impl<'a> Minimult<'a>
     /// * `mem` - reserved memory block.
     /// * `num_tasks` - number of tasks.
     /// * Returns the created instance.
-    /// * (`num_tasks` * (32 + 4)) bytes of the memory block is consumed.
+    /// * (`num_tasks` * (80 + 4)) bytes of the memory block is consumed.
     pub fn new<B>(mem: &mut MTMemBlk<B>, num_tasks: MTTaskId) -> Minimult
     {
         let mut alloc = MTAlloc::new(mem);
@@ -51,6 +86,75 @@ impl<'a> Minimult<'a>
         MTMsgQueue::new(mem)
     }
 
+    /// Creates a priority message queue.
+    /// * `K` - type of the priority key.
+    /// * `M` - type of the message element.
+    /// * `len` - length of the message queue array.
+    /// * Returns the created priority message queue.
+    /// * (`len` * ((size of `M`) + (size of `(usize, Option<(K, usize)>)`))) bytes of the memory block is consumed.
+    pub fn prioq<K: Ord + Copy, M>(&mut self, len: usize) -> MTPrioQueue<'a, K, M> // NOTE: lifetime safety correctness
+    {
+        let mem = self.alloc.array(len);
+        let tree_array = self.alloc.array(len);
+
+        MTPrioQueue::new(mem, tree_array)
+    }
+
+    /// Creates a rendezvous channel.
+    /// * `M` - type of the message.
+    /// * Returns the created rendezvous channel, which has no buffer of messages.
+    pub fn rendezvous<M>(&mut self) -> MTRendezvous<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTRendezvous::new()
+    }
+
+    /// Creates a broadcast channel.
+    /// * `M` - type of the message element.
+    /// * `len` - length of the message ring array.
+    /// * Returns the created broadcast channel.
+    /// * (`len` * (size of `M`)) bytes of the memory block is consumed.
+    pub fn broadcast<M: Clone>(&mut self, len: usize) -> MTBroadcast<'a, M> // NOTE: lifetime safety correctness
+    {
+        let mem = self.alloc.array(len);
+
+        MTBroadcast::new(mem)
+    }
+
+    /// Creates a stream buffer.
+    /// * `len` - number of bytes of the buffer.
+    /// * Returns the created stream buffer.
+    /// * `len` bytes of the memory block is consumed.
+    pub fn stream_buffer(&mut self, len: usize) -> MTStreamBuffer<'a> // NOTE: lifetime safety correctness
+    {
+        let mem = self.alloc.array(len);
+
+        MTStreamBuffer::new(mem)
+    }
+
+    /// Creates a message buffer.
+    /// * `len` - number of bytes of the buffer, including a 2-byte length prefix for each frame.
+    /// * Returns the created message buffer.
+    /// * `len` bytes of the memory block is consumed.
+    pub fn message_buffer(&mut self, len: usize) -> MTMessageBuffer<'a> // NOTE: lifetime safety correctness
+    {
+        let mem = self.alloc.array(len);
+
+        MTMessageBuffer::new(mem)
+    }
+
+    /// Creates a fixed-size block pool.
+    /// * `V` - type of the block.
+    /// * `len` - number of the blocks.
+    /// * Returns the created pool.
+    /// * (`len` * (size of `V` + size of `usize`)) bytes of the memory block is consumed.
+    pub fn pool<V>(&mut self, len: usize) -> MTPool<'a, V> // NOTE: lifetime safety correctness
+    {
+        let blks = self.alloc.array(len);
+        let free = self.alloc.array(len);
+
+        MTPool::new(blks, free)
+    }
+
     /// Creates a shared variable.
     /// * `m: M` - the variable to be shared.
     /// * Returns the created shared variable.
@@ -59,12 +163,124 @@ impl<'a> Minimult<'a>
         MTShared::new(m)
     }
 
+    /// Creates a watch channel.
+    /// * `init: M` - the initial value.
+    /// * Returns the created watch channel.
+    pub fn watch<M: Copy>(&mut self, init: M) -> MTWatch<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTWatch::new(init)
+    }
+
+    /// Creates a mutex with priority inheritance.
+    /// * `m: M` - the variable to be protected.
+    /// * Returns the created mutex.
+    pub fn mutex<M>(&mut self, m: M) -> MTMutex<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTMutex::new(m)
+    }
+
+    /// Creates a resource locked with the immediate priority ceiling protocol.
+    /// * `ceiling` - ceiling priority, which must be the highest priority of tasks locking the resource.
+    /// * `m: M` - the variable to be protected.
+    /// * Returns the created resource.
+    pub fn resource<M>(&mut self, ceiling: MTTaskPri, m: M) -> MTResource<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTResource::new(ceiling, m)
+    }
+
+    /// Creates a semaphore.
+    /// * `init` - initial count.
+    /// * `max` - maximum count. `1` for a binary semaphore.
+    /// * Returns the created semaphore.
+    pub fn semaphore(&mut self, init: usize, max: usize) -> MTSemaphore<'a> // NOTE: lifetime safety correctness
+    {
+        MTSemaphore::new(init, max)
+    }
+
+    /// Creates an event flag group.
+    /// * `init` - initial bits.
+    /// * Returns the created event flag group.
+    pub fn event_flags(&mut self, init: u32) -> MTEventFlags<'a> // NOTE: lifetime safety correctness
+    {
+        MTEventFlags::new(init)
+    }
+
+    /// Creates a condition variable.
+    /// * Returns the created condition variable.
+    pub fn condvar(&mut self) -> MTCondvar<'a> // NOTE: lifetime safety correctness
+    {
+        MTCondvar::new()
+    }
+
+    /// Creates a barrier.
+    /// * `num` - number of tasks to be synchronized.
+    /// * Returns the created barrier.
+    pub fn barrier(&mut self, num: usize) -> MTBarrier<'a> // NOTE: lifetime safety correctness
+    {
+        MTBarrier::new(num)
+    }
+
+    /// Creates a software timer.
+    /// * `f: F` - closure called back in the timer service task when the timer expires.
+    /// * Returns the created timer, which is inactive until started.
+    /// * The timer service task must be registered by `timer_service` in advance.
+    /// * (28 + size of `F`) bytes of the memory block is consumed.
+    pub fn timer<F>(&mut self, f: F) -> MTTimer<'a> // NOTE: lifetime safety correctness
+    where F: FnMut() + Send + 'a
+    {
+        assert!(mttimersvc_exists(),
+                "timer service: not registered");
+
+        let mem = self.alloc.array(1_usize);
+        let mem_f = self.alloc.array(1_usize);
+
+        MTTimer::new(mem, mem_f, f)
+    }
+
+    /// Enables MPU stack guard regions.
+    /// * Must be called before registering tasks.
+    /// * Only for `thumbv7m-*` and `thumbv7em-*` targets; has no effect for other targets.
+    /// * A no-access guard region is placed at the lowest 32-byte aligned 32 bytes within the running task stack
+    ///   at every task switch, so that an overflow is caught by `MemoryManagement` exception
+    ///   and reported as a panic with its task identifier.
+    /// * The guard region is within the stack, so the usable stack shrinks by up to 32 bytes
+    ///   plus the alignment loss of up to 28 bytes; `stack_len` should have room for it.
+    /// * `MemoryManagement` exception handler is provided by `Minimult` only with `mem-manage-handler` feature,
+    ///   which disables the MPU and panics at any `MemManage` fault;
+    ///   otherwise an overflow goes to the application's own handler or `DefaultHandler`.
+    /// * *NOTE: An overflow by interrupt handlers escalates to `HardFault` instead,
+    ///   because no exception preempts each other.*
+    pub fn stack_guard(&mut self)
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        assert!(tm.none_registered(),
+                "stack guard: tasks already registered");
+
+        tm.stack_guard();
+    }
+
+    /// Registers a timer service task which calls back closures of software timers.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * `pri` - task priority. The lower value is the higher priority.
+    /// * `stack_len` - length of a stack used by the task, which must be enough for the closures.
+    /// * Active timers are kept sorted in order of expiry,
+    ///   so that the task wakes up only when the nearest timer expires.
+    /// * (`stack_len` * size of `usize`) bytes of the memory block is consumed.
+    pub fn timer_service(&mut self, tid: MTTaskId, pri: MTTaskPri, stack_len: usize)
+    {
+        mttimersvc_create();
+
+        self.register(tid, pri, stack_len, mttimersvc_loop);
+    }
+
     /// Registers a closure as a task.
     /// * `tid` - task identifier. `0` to `num_tasks - 1`.
     /// * `pri` - task priority. The lower value is the higher priority.
     /// * `stack_len` - length of a stack used by the task.
     ///   * `Minimult` kernel performs stack checks when task-switching.
     ///     If a target is `thumbv8m.*`, `MSPLIM` stack limit check is also enabled.
+    ///     If a target is `thumbv7m-*` or `thumbv7em-*`, MPU guard region check is enabled by `stack_guard`.
     /// * `task: T` - task closure.
     /// * (`stack_len` * size of `usize`) bytes of the memory block is consumed.
     pub fn register<T>(&mut self, tid: MTTaskId, pri: MTTaskPri, stack_len: usize, task: T)
@@ -74,7 +290,35 @@ impl<'a> Minimult<'a>
 
         let stack = self.alloc.array(stack_len);
         
-        tm.register_once(tid, pri, stack, task);
+        tm.register_once(tid, pri, stack.head(), stack.tail(), null_mut(), task);
+    }
+
+    /// Registers a closure returning a value as a task.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * `pri` - task priority. The lower value is the higher priority.
+    /// * `stack_len` - length of a stack used by the task.
+    /// * `task: T` - task closure, which returns a value of `V`.
+    /// * Returns a join handle of the task.
+    /// * The return value is kept at the tail of the stack.
+    /// * (`stack_len` * size of `usize`) bytes of the memory block is consumed.
+    pub fn register_join<T, V>(&mut self, tid: MTTaskId, pri: MTTaskPri, stack_len: usize, task: T) -> MTJoinHandle<'a, V>
+    where T: FnOnce() -> V + Send + 'a, V: Send + 'a // NOTE: lifetime safety correctness
+    {
+        let stack = self.alloc.array(stack_len);
+
+        MTJoinHandle::register(tid, pri, stack, task)
+    }
+
+    /// Reserves a stack to spawn a task dynamically.
+    /// * `stack_len` - length of a stack used by the task.
+    /// * Returns the reserved stack.
+    /// * The stack can be reused after the task spawned on it finishes.
+    /// * (`stack_len` * size of `usize`) bytes of the memory block is consumed.
+    pub fn stack(&mut self, stack_len: usize) -> MTStack<'a> // NOTE: lifetime safety correctness
+    {
+        let mem = self.alloc.array(stack_len);
+
+        MTStack::new(mem)
     }
 
     /// Runs into a loop to dispatch the registered tasks.
@@ -94,14 +338,177 @@ impl<'a> Minimult<'a>
     pub fn idle()
     {
         if let Some(tm) = mtkernel_get_mut() {
-            tm.idle();
+            tm.idle(None);
+        }
+    }
+
+    /// Brings a current running task into an idle state with a timeout.
+    /// * `ticks` - number of `tick`s until the timeout.
+    /// * Returns `Err(MTTimeout)` if not kicked within the timeout.
+    pub fn idle_timeout(ticks: usize) -> Result<(), MTTimeout>
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            let from = tm.tick_count();
+
+            if !tm.idle(Some((from, ticks))) {
+                return Err(MTTimeout(()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits for a notification to a current running task.
+    /// * Returns the notification value, which is then cleared to zero.
+    /// * Blocks until notified by `notify`.
+    pub fn wait_notify() -> u32
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            tm.wait_notify(None).bk_unwrap()
+        }
+        else {
+            0
+        }
+    }
+
+    /// Waits for a notification to a current running task with a timeout.
+    /// * `ticks` - number of `tick`s until the timeout.
+    /// * Returns the notification value, which is then cleared to zero.
+    /// * Blocks until notified by `notify`.
+    /// * Returns `Err(MTTimeout)` if not notified within the timeout.
+    pub fn wait_notify_timeout(ticks: usize) -> Result<u32, MTTimeout>
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            let from = tm.tick_count();
+
+            tm.wait_notify(Some((from, ticks))).ok_or(MTTimeout(()))
+        }
+        else {
+            Err(MTTimeout(()))
         }
     }
 
+    /// Joins a task registered by `register_join` or spawned on `MTStack`.
+    /// * `handle` - join handle of the task.
+    /// * Returns the return value of the task.
+    /// * Blocks until the task finishes.
+    pub fn join<V>(handle: MTJoinHandle<'_, V>) -> V
+    {
+        handle.join()
+    }
+
+    /// Tries to join a task registered by `register_join` or spawned on `MTStack`.
+    /// * `handle` - join handle of the task.
+    /// * Returns the return value of the task in `Result`.
+    /// * Gets `Err` giving back the handle if the task is not finished yet.
+    pub fn try_join<V>(handle: MTJoinHandle<'_, V>) -> Result<V, MTJoinHandle<'_, V>>
+    {
+        handle.try_join()
+    }
+
+    /// Brings a current running task into a delayed state for a number of ticks.
+    /// * `ticks` - number of `tick`s to be delayed.
+    /// * Must be called from a running task, not before `run` nor from interrupt handlers.
+    ///   `MTDelay` spins instead in such cases.
+    pub fn delay(ticks: usize)
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            assert!(Minimult::in_task(),
+                    "delay: not in task context");
+
+            let from = tm.tick_count();
+            tm.delay(from, ticks);
+        }
+    }
+
+    /// Brings a current running task into a delayed state until a periodic wake-up time.
+    /// * `last_wake` - tick count when the task last woke up. Updated to the next wake-up time.
+    ///   Initialize it with `tick_count` before a periodic loop.
+    /// * `period` - number of `tick`s of the period.
+    /// * Unlike `delay`, the period doesn't drift even if the task is delayed to run.
+    /// * Must be called from a running task, not before `run` nor from interrupt handlers.
+    pub fn delay_until(last_wake: &mut usize, period: usize)
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            assert!(Minimult::in_task(),
+                    "delay: not in task context");
+
+            tm.delay(*last_wake, period);
+            *last_wake = last_wake.wrapping_add(period);
+        }
+    }
+
+    pub(crate) fn in_task() -> bool
+    {
+        let thread = cortex_m::peripheral::SCB::vect_active() == cortex_m::peripheral::scb::VectActive::ThreadMode;
+
+        thread && Minimult::curr_tid().is_some()
+    }
+
     pub(crate) fn wait(ev: &MTEvent, evcond: MTEventCond)
     {
         if let Some(tm) = mtkernel_get_mut() {
-            tm.wait(ev, evcond);
+            tm.wait(ev, evcond, None);
+        }
+    }
+
+    pub(crate) fn wait_timeout(ev: &MTEvent, evcond: MTEventCond, from: usize, ticks: usize) -> bool
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            if tick_elapsed(tm.tick_count(), from, ticks) {
+                return false;
+            }
+
+            tm.wait(ev, evcond, Some((from, ticks)));
+        }
+
+        true
+    }
+
+    pub(crate) fn mutex_lock(mtx: &mut MTMutexCore, block: bool) -> bool
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        tm.mutex_lock(mtx, block)
+    }
+
+    pub(crate) fn mutex_cancel(mtx: &MTMutexCore)
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        tm.mutex_cancel(mtx);
+    }
+
+    pub(crate) fn mutex_unlock(mtx: &mut MTMutexCore)
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        tm.mutex_unlock(mtx);
+    }
+
+    pub(crate) fn ceiling_lock(ceil: &mut MTCeilingCore) -> bool
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        tm.ceiling_lock(ceil)
+    }
+
+    pub(crate) fn ceiling_unlock(ceil: &mut MTCeilingCore)
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        tm.ceiling_unlock(ceil);
+    }
+
+    pub(crate) fn select(evs: &[MTEventWait], kick: bool, tmo: Option<usize>) -> Option<Option<usize>>
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            let tmo = tmo.map(|ticks| (tm.tick_count(), ticks));
+
+            tm.select(evs, kick, tmo)
+        }
+        else {
+            None
         }
     }
 
@@ -131,6 +538,103 @@ impl<'a> Minimult<'a>
         }
     }
 
+    /// Notifies a task with a value.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * `value` - value applied to the notification value of the task by `action`.
+    /// * `action` - how to update the notification value.
+    /// * Wakes up the task waiting by `wait_notify`.
+    /// * Returns `false` if not notified because `MTNotifyAction::OverwriteIfEmpty` finds a pending notification.
+    pub fn notify(tid: MTTaskId, value: u32, action: MTNotifyAction) -> bool
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            tm.notify(tid, value, action)
+        }
+        else {
+            false
+        }
+    }
+
+    /// Suspends a task.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * The task is not dispatched until `resume`d, even if a waiting condition of the task is satisfied.
+    /// * Nothing happens if the task is not registered or already finished.
+    pub fn suspend(tid: MTTaskId)
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            tm.suspend(tid);
+        }
+    }
+
+    /// Resumes a suspended task.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * If the task was waiting, a wake-up occurred during the suspension takes effect.
+    /// * Nothing happens if the task is not suspended.
+    pub fn resume(tid: MTTaskId)
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            tm.resume(tid);
+        }
+    }
+
+    /// Changes priority of a task.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * `pri` - task priority. The lower value is the higher priority.
+    /// * Dispatching is requested if the running task gets outranked.
+    pub fn set_priority(tid: MTTaskId, pri: MTTaskPri)
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            tm.set_priority(tid, pri);
+        }
+    }
+
+    /// Gets priority of a task.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * Returns the task priority given at registration or by `set_priority`, not inherited or ceiling one.
+    pub fn get_priority(tid: MTTaskId) -> MTTaskPri
+    {
+        let tm = mtkernel_get_ref().bk_unwrap();
+
+        tm.get_priority(tid)
+    }
+
+    /// Advances a kernel tick count by one.
+    /// * Typically called from `SysTick` exception handler.
+    /// * Timed-waiting tasks are kept in order of their deadlines,
+    ///   and a service call to request dispatching is made only when the earliest ones expire.
+    /// * Tasks of the same priority are not time-sliced by `tick`; call `dispatch` as well for that.
+    pub fn tick()
+    {
+        if let Some(tm) = mtkernel_get_mut() {
+            tm.tick();
+        }
+    }
+
+    /// Gets a stack high-water mark of a task.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * Returns the minimum number of free words the stack has ever had, except a canary word or a guard region at the bottom.
+    /// * Gets `0` if the task has never been registered or spawned.
+    pub fn stack_high_water(tid: MTTaskId) -> usize
+    {
+        if let Some(tm) = mtkernel_get_ref() {
+            tm.stack_high_water(tid)
+        }
+        else {
+            0
+        }
+    }
+
+    /// Gets a kernel tick count.
+    /// * Returns the number of `tick`s, wrapping around.
+    pub fn tick_count() -> usize
+    {
+        if let Some(tm) = mtkernel_get_ref() {
+            tm.tick_count()
+        }
+        else {
+            0
+        }
+    }
+
     /// Gets task identifier of a current running task if any.
     /// * Returns task identifier in `Option`.
     pub fn curr_tid() -> Option<MTTaskId>
@@ -1,8 +1,8 @@
 use core::marker::PhantomData;
 
-use crate::minimult::Minimult;
+use crate::minimult::{Minimult, MTTimeout};
 use crate::memory::MTRawArray;
-use crate::kernel::{MTEvent, MTEventCond};
+use crate::kernel::{MTEvent, MTEventCond, MTEventWait};
 use crate::bkptpanic::BKUnwrap;
 
 //
@@ -40,6 +40,7 @@ impl<'a, M> MTMsgQueue<'a, M>
 
     /// Gets sending and receving channels.
     /// * Returns a tuple of the sender and receiver pair.
+    /// * The sender can be cloned for multiple producers.
     pub fn ch<'q>(&'q mut self) -> (MTMsgSender<'a, 'q, M>, MTMsgReceiver<'a, 'q, M>)
     {
         (
@@ -58,6 +59,7 @@ impl<'a, M> MTMsgQueue<'a, M>
 //
 
 /// Message sending channel
+/// * Cloneable so that multiple tasks send messages to the same queue.
 pub struct MTMsgSender<'a, 'q, M>
 {
     q: *mut MTMsgQueue<'a, M>,
@@ -66,6 +68,17 @@ pub struct MTMsgSender<'a, 'q, M>
 
 unsafe impl<M: Send> Send for MTMsgSender<'_, '_, M> {}
 
+impl<M> Clone for MTMsgSender<'_, '_, M>
+{
+    fn clone(&self) -> Self
+    {
+        MTMsgSender {
+            q: self.q,
+            phantom: PhantomData
+        }
+    }
+}
+
 impl<M> MTMsgSender<'_, '_, M>
 {
     /// Gets if there is a vacant message entry.
@@ -80,27 +93,89 @@ impl<M> MTMsgSender<'_, '_, M>
     /// Sends a message.
     /// * `msg` - the message to be sent.
     /// * Blocks if there is no vacant message entry.
-    pub fn send(&mut self, msg: M)
+    /// * Senders blocked by other senders are released in order of their priorities.
+    pub fn send(&mut self, mut msg: M)
     {
         let q = unsafe { self.q.as_mut().bk_unwrap() };
 
         loop {
-            if q.msg_cnt.cnt() < q.mem.len() {
-                break;
+            match self.push(msg) {
+                Ok(()) => return,
+                Err(m) => msg = m
             }
 
             Minimult::wait(&q.msg_cnt, MTEventCond::LessThan(q.mem.len()));
         }
+    }
 
-        let curr_wr_idx = q.wr_idx;
-        let next_wr_idx = wrap_inc(curr_wr_idx, q.mem.len());
+    /// Sends a message with a timeout.
+    /// * `msg` - the message to be sent.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Blocks if there is no vacant message entry.
+    /// * Returns `Err(MTTimeout(msg))` giving back the message if no entry gets vacant within the timeout.
+    pub fn send_timeout(&mut self, mut msg: M, ticks: usize) -> Result<(), MTTimeout<M>>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
 
-        q.mem.write_volatile(curr_wr_idx, msg);
+        let from = Minimult::tick_count();
 
-        q.wr_idx = next_wr_idx; // NOTE: volatile access might be necessary
+        loop {
+            match self.push(msg) {
+                Ok(()) => return Ok(()),
+                Err(m) => msg = m
+            }
 
-        q.msg_cnt.incr();
-        Minimult::signal(&q.msg_cnt);
+            if !Minimult::wait_timeout(&q.msg_cnt, MTEventCond::LessThan(q.mem.len()), from, ticks) {
+                return Err(MTTimeout(msg));
+            }
+        }
+    }
+
+    /// Tries to send a message.
+    /// * `msg` - the message to be sent.
+    /// * Returns `Err(msg)` giving back the message if there is no vacant message entry.
+    pub fn try_send(&mut self, msg: M) -> Result<(), M>
+    {
+        self.push(msg)
+    }
+
+    /// Sends a message from an interrupt handler.
+    /// * `msg` - the message to be sent.
+    /// * Never blocks but returns `Err(msg)` giving back the message if there is no vacant message entry.
+    /// * Wakes up the task waiting for the message.
+    pub fn send_from_isr(&self, msg: M) -> Result<(), M>
+    {
+        self.push(msg)
+    }
+
+    fn push(&self, msg: M) -> Result<(), M>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        // NOTE: exclusive among senders and interrupts
+        let r = cortex_m::interrupt::free(|_| {
+            if q.msg_cnt.cnt() < q.mem.len() {
+                let curr_wr_idx = q.wr_idx;
+                let next_wr_idx = wrap_inc(curr_wr_idx, q.mem.len());
+
+                q.mem.write_volatile(curr_wr_idx, msg);
+
+                q.wr_idx = next_wr_idx; // NOTE: volatile access might be necessary
+
+                q.msg_cnt.incr();
+
+                Ok(())
+            }
+            else {
+                Err(msg)
+            }
+        });
+
+        if r.is_ok() {
+            Minimult::signal(&q.msg_cnt);
+        }
+
+        r
     }
 }
 
@@ -141,6 +216,77 @@ impl<M> MTMsgReceiver<'_, '_, M>
             Minimult::wait(&q.msg_cnt, MTEventCond::GreaterThan(0));
         }
 
+        self.pop()
+    }
+
+    /// Receives a message with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the received message.
+    /// * Blocks if there is no available message entry.
+    /// * Returns `Err(MTTimeout)` if no message gets available within the timeout.
+    pub fn receive_timeout(&mut self, ticks: usize) -> Result<M, MTTimeout>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if q.msg_cnt.cnt() > 0 {
+                break;
+            }
+
+            if !Minimult::wait_timeout(&q.msg_cnt, MTEventCond::GreaterThan(0), from, ticks) {
+                return Err(MTTimeout(()));
+            }
+        }
+
+        Ok(self.pop())
+    }
+
+    /// Tries to receive a message.
+    /// * Returns the received message in `Option`.
+    /// * Gets `None` if there is no available message entry.
+    /// * Can be called from interrupt handlers.
+    pub fn try_receive(&mut self) -> Option<M>
+    {
+        if self.available() > 0 {
+            Some(self.pop())
+        }
+        else {
+            None
+        }
+    }
+
+    /// Peeks a message without receiving it.
+    /// * Returns a reference to the message to be received next in `Option`.
+    /// * Gets `None` if there is no available message entry.
+    /// * Can be called from interrupt handlers.
+    pub fn peek(&self) -> Option<&M>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        if q.msg_cnt.cnt() > 0 {
+            Some(q.mem.refer(q.rd_idx))
+        }
+        else {
+            None
+        }
+    }
+
+    pub(crate) fn available_wait(&self) -> MTEventWait
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        MTEventWait {
+            ev: &q.msg_cnt,
+            cond: MTEventCond::GreaterThan(0)
+        }
+    }
+
+    fn pop(&mut self) -> M
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
         let curr_rd_idx = q.rd_idx;
         let next_rd_idx = wrap_inc(curr_rd_idx, q.mem.len());
 
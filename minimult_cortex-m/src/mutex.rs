@@ -0,0 +1,152 @@
+use core::marker::PhantomData;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::kernel::{MTMutexCore, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Mutex with priority inheritance
+pub struct MTMutex<'a, M>
+{
+    holder: M,
+    core: MTMutexCore,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a, M> MTMutex<'a, M>
+{
+    pub(crate) fn new(holder: M) -> MTMutex<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTMutex {
+            holder,
+            core: MTMutexCore::new(),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets a mutex access channel.
+    /// * Returns the mutex access channel.
+    pub fn ch<'s>(&'s self) -> MTMutexCh<'a, 's, M>
+    {
+        MTMutexCh {
+            m: (self as *const Self) as *mut Self, // NOTE: mutability conversion
+            phantom: PhantomData
+        }
+    }
+}
+
+//
+
+/// Mutex access channel
+pub struct MTMutexCh<'a, 's, M>
+{
+    m: *mut MTMutex<'a, M>,
+    phantom: PhantomData<&'s ()>
+}
+
+unsafe impl<M: Send> Send for MTMutexCh<'_, '_, M> {}
+
+impl<M> MTMutexCh<'_, '_, M>
+{
+    /// Locks a mutex.
+    /// * Returns a `DerefMut`-able wrapper of the variable in the mutex.
+    /// * Blocks if the mutex is locked by other channels.
+    ///   Meanwhile the task holding the mutex inherits the priority of this task if higher.
+    /// * Must not be locked again by the same task before unlocked.
+    pub fn lock<'c>(&'c self) -> MTMutexGuard<'c, M>
+    {
+        let m = unsafe { self.m.as_mut().bk_unwrap() };
+
+        loop {
+            if Minimult::mutex_lock(&mut m.core, true) {
+                return MTMutexGuard {
+                    holder: &mut m.holder,
+                    core: &mut m.core
+                };
+            }
+
+            Minimult::wait(m.core.lock_ev(), MTEventCond::Equal(0));
+        }
+    }
+
+    /// Locks a mutex with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns a `DerefMut`-able wrapper of the variable in the mutex.
+    /// * Blocks if the mutex is locked by other channels.
+    ///   Meanwhile the task holding the mutex inherits the priority of this task if higher.
+    /// * Returns `Err(MTTimeout)` if the mutex doesn't get unlocked within the timeout,
+    ///   and the task holding the mutex no longer inherits the priority of this task.
+    /// * Must not be locked again by the same task before unlocked.
+    pub fn lock_timeout<'c>(&'c self, ticks: usize) -> Result<MTMutexGuard<'c, M>, MTTimeout>
+    {
+        let m = unsafe { self.m.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if Minimult::mutex_lock(&mut m.core, true) {
+                return Ok(MTMutexGuard {
+                    holder: &mut m.holder,
+                    core: &mut m.core
+                });
+            }
+
+            if !Minimult::wait_timeout(m.core.lock_ev(), MTEventCond::Equal(0), from, ticks) {
+                Minimult::mutex_cancel(&m.core);
+                return Err(MTTimeout(()));
+            }
+        }
+    }
+
+    /// Tries to lock a mutex.
+    /// * Returns a `DerefMut`-able wrapper of the variable in the mutex in `Option`.
+    /// * Gets `None` if the mutex is locked by other channels.
+    pub fn try_lock<'c>(&'c self) -> Option<MTMutexGuard<'c, M>>
+    {
+        let m = unsafe { self.m.as_mut().bk_unwrap() };
+
+        if Minimult::mutex_lock(&mut m.core, false) {
+            Some(MTMutexGuard {
+                holder: &mut m.holder,
+                core: &mut m.core
+            })
+        }
+        else {
+            None
+        }
+    }
+}
+
+//
+
+/// Mutex's lock scope wrapper
+pub struct MTMutexGuard<'c, M>
+{
+    holder: &'c mut M,
+    core: &'c mut MTMutexCore,
+}
+
+impl<M> core::ops::Deref for MTMutexGuard<'_, M>
+{
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target
+    {
+        self.holder
+    }
+}
+
+impl<M> core::ops::DerefMut for MTMutexGuard<'_, M>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        self.holder
+    }
+}
+
+impl<M> Drop for MTMutexGuard<'_, M>
+{
+    fn drop(&mut self)
+    {
+        Minimult::mutex_unlock(self.core);
+    }
+}
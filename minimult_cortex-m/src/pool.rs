@@ -0,0 +1,248 @@
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::memory::MTRawArray;
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Fixed-size block pool
+pub struct MTPool<'a, V>
+{
+    blks: MTRawArray<V>,
+    free: MTRawArray<usize>, // stack of free block indices
+    free_cnt: MTEvent,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl<V: Send> Sync for MTPool<'_, V> {}
+
+impl<'a, V> MTPool<'a, V>
+{
+    pub(crate) fn new(blks: MTRawArray<V>, free: MTRawArray<usize>) -> MTPool<'a, V> // NOTE: lifetime safety correctness
+    {
+        for i in 0..free.len() {
+            free.write(i, i);
+        }
+
+        MTPool {
+            free_cnt: MTEvent::new(free.len()),
+            blks,
+            free,
+            phantom: PhantomData
+        }
+    }
+}
+
+impl<V> MTPool<'_, V>
+{
+    /// Gets if there is a free block.
+    /// * Returns the number of free blocks.
+    pub fn available(&self) -> usize
+    {
+        self.free_cnt.cnt()
+    }
+
+    /// Allocates a block.
+    /// * `v: V` - the value to be moved into the block.
+    /// * Returns an owner of the block, which returns the block to the pool when dropped.
+    /// * Blocks if there is no free block.
+    pub fn alloc(&self, mut v: V) -> MTPoolBox<'_, V>
+    {
+        loop {
+            match self.try_alloc(v) {
+                Ok(b) => return b,
+                Err(r) => v = r
+            }
+
+            Minimult::wait(&self.free_cnt, MTEventCond::GreaterThan(0));
+        }
+    }
+
+    /// Allocates a block with a timeout.
+    /// * `v: V` - the value to be moved into the block.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns an owner of the block, which returns the block to the pool when dropped.
+    /// * Blocks if there is no free block.
+    /// * Returns `Err(MTTimeout(v))` giving back the value if no block gets free within the timeout.
+    pub fn alloc_timeout(&self, mut v: V, ticks: usize) -> Result<MTPoolBox<'_, V>, MTTimeout<V>>
+    {
+        let from = Minimult::tick_count();
+
+        loop {
+            match self.try_alloc(v) {
+                Ok(b) => return Ok(b),
+                Err(r) => v = r
+            }
+
+            if !Minimult::wait_timeout(&self.free_cnt, MTEventCond::GreaterThan(0), from, ticks) {
+                return Err(MTTimeout(v));
+            }
+        }
+    }
+
+    /// Tries to allocate a block.
+    /// * `v: V` - the value to be moved into the block.
+    /// * Returns an owner of the block, which returns the block to the pool when dropped.
+    /// * Returns `Err(v)` giving back the value if there is no free block.
+    /// * Can be called from interrupt handlers.
+    pub fn try_alloc(&self, v: V) -> Result<MTPoolBox<'_, V>, V>
+    {
+        if let Some(idx) = self.take() {
+            self.blks.write(idx, v);
+
+            Ok(MTPoolBox {
+                pool: self,
+                idx,
+                phantom: PhantomData
+            })
+        }
+        else {
+            Err(v)
+        }
+    }
+
+    /// Allocates a block and initializes it in place.
+    /// * `init: F` - closure which initializes the given block and returns the reference to the initialized value.
+    /// * Returns an owner of the block, which returns the block to the pool when dropped.
+    /// * Blocks if there is no free block.
+    /// * A large value can be built directly in the block instead of being moved from the stack.
+    pub fn alloc_with<F>(&self, mut init: F) -> MTPoolBox<'_, V>
+    where F: FnOnce(&mut MaybeUninit<V>) -> &mut V
+    {
+        loop {
+            match self.try_alloc_with(init) {
+                Ok(b) => return b,
+                Err(r) => init = r
+            }
+
+            Minimult::wait(&self.free_cnt, MTEventCond::GreaterThan(0));
+        }
+    }
+
+    /// Allocates a block and initializes it in place with a timeout.
+    /// * `init: F` - closure which initializes the given block and returns the reference to the initialized value.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns an owner of the block, which returns the block to the pool when dropped.
+    /// * Blocks if there is no free block.
+    /// * Returns `Err(MTTimeout(init))` giving back the closure if no block gets free within the timeout.
+    pub fn alloc_with_timeout<F>(&self, mut init: F, ticks: usize) -> Result<MTPoolBox<'_, V>, MTTimeout<F>>
+    where F: FnOnce(&mut MaybeUninit<V>) -> &mut V
+    {
+        let from = Minimult::tick_count();
+
+        loop {
+            match self.try_alloc_with(init) {
+                Ok(b) => return Ok(b),
+                Err(r) => init = r
+            }
+
+            if !Minimult::wait_timeout(&self.free_cnt, MTEventCond::GreaterThan(0), from, ticks) {
+                return Err(MTTimeout(init));
+            }
+        }
+    }
+
+    /// Tries to allocate a block and initialize it in place.
+    /// * `init: F` - closure which initializes the given block and returns the reference to the initialized value.
+    /// * Returns an owner of the block, which returns the block to the pool when dropped.
+    /// * Returns `Err(init)` giving back the closure if there is no free block.
+    /// * Can be called from interrupt handlers.
+    pub fn try_alloc_with<F>(&self, init: F) -> Result<MTPoolBox<'_, V>, F>
+    where F: FnOnce(&mut MaybeUninit<V>) -> &mut V
+    {
+        if let Some(idx) = self.take() {
+            let blk = unsafe { self.blks.head().add(idx) } as *mut MaybeUninit<V>;
+            let blk = unsafe { blk.as_mut().bk_unwrap() };
+
+            let v: *mut V = init(blk);
+
+            assert!(v == blk.as_mut_ptr(),
+                    "pool: block not initialized in place");
+
+            Ok(MTPoolBox {
+                pool: self,
+                idx,
+                phantom: PhantomData
+            })
+        }
+        else {
+            Err(init)
+        }
+    }
+
+    fn take(&self) -> Option<usize>
+    {
+        let free_cnt = (&self.free_cnt as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let free_cnt = unsafe { free_cnt.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| {
+            if free_cnt.cnt() > 0 {
+                free_cnt.decr();
+                Some(self.free.read(free_cnt.cnt()))
+            }
+            else {
+                None
+            }
+        })
+    }
+
+    fn release(&self, idx: usize)
+    {
+        let free_cnt = (&self.free_cnt as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let free_cnt = unsafe { free_cnt.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| {
+            self.free.write(free_cnt.cnt(), idx);
+            free_cnt.incr();
+        });
+
+        Minimult::signal(free_cnt);
+    }
+}
+
+//
+
+/// Owner of a block allocated from `MTPool`
+/// * Can be sent through `MTMsgQueue` without copying the block content.
+pub struct MTPoolBox<'p, V>
+{
+    pool: *const MTPool<'p, V>,
+    idx: usize,
+    phantom: PhantomData<&'p ()>
+}
+
+unsafe impl<V: Send> Send for MTPoolBox<'_, V> {}
+
+impl<V> core::ops::Deref for MTPoolBox<'_, V>
+{
+    type Target = V;
+
+    fn deref(&self) -> &Self::Target
+    {
+        let pool = unsafe { self.pool.as_ref().bk_unwrap() };
+
+        pool.blks.refer(self.idx)
+    }
+}
+
+impl<V> core::ops::DerefMut for MTPoolBox<'_, V>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        let pool = unsafe { self.pool.as_ref().bk_unwrap() };
+
+        pool.blks.refer(self.idx)
+    }
+}
+
+impl<V> Drop for MTPoolBox<'_, V>
+{
+    fn drop(&mut self)
+    {
+        let pool = unsafe { self.pool.as_ref().bk_unwrap() };
+
+        drop(pool.blks.read(self.idx));
+        pool.release(self.idx);
+    }
+}
@@ -0,0 +1,286 @@
+use core::marker::PhantomData;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::memory::MTRawArray;
+use crate::bheap::MTBHeapDList;
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+//
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MTPrioSeqKey<K>
+{
+    key: K,
+    seq: usize
+}
+
+impl<K: Ord> Ord for MTPrioSeqKey<K>
+{
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering
+    {
+        // FIFO among the same keys, even at wrapping-around of seq
+        self.key.cmp(&other.key).then_with(|| (self.seq.wrapping_sub(other.seq) as isize).cmp(&0))
+    }
+}
+
+impl<K: Ord> PartialOrd for MTPrioSeqKey<K>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+pub(crate) type MTPrioKey<K> = Option<MTPrioSeqKey<K>>; // NOTE: None until first queued, then stale in the free list
+
+/// Message queue which receives messages in order of priority keys
+pub struct MTPrioQueue<'a, K, M>
+{
+    mem: MTRawArray<M>,
+    tree: MTBHeapDList<usize, MTPrioKey<K>>, // bheap of queued slots followed by flist of vacant slots
+    seq: usize,
+    msg_cnt: MTEvent,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a, K: Ord + Copy, M> MTPrioQueue<'a, K, M>
+{
+    pub(crate) fn new(mem: MTRawArray<M>, tree_array: MTRawArray<(usize, MTPrioKey<K>)>) -> MTPrioQueue<'a, K, M> // NOTE: lifetime safety correctness
+    {
+        let mut tree = MTBHeapDList::new(tree_array);
+
+        for i in 0..mem.len() {
+            tree.add_flist(i, None);
+        }
+
+        MTPrioQueue {
+            mem,
+            tree,
+            seq: 0,
+            msg_cnt: MTEvent::new(0),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets sending and receving channels.
+    /// * Returns a tuple of the sender and receiver pair.
+    /// * The sender can be cloned for multiple producers.
+    pub fn ch<'q>(&'q mut self) -> (MTPrioSender<'a, 'q, K, M>, MTPrioReceiver<'a, 'q, K, M>)
+    {
+        (
+            MTPrioSender {
+                q: self,
+                phantom: PhantomData
+            },
+            MTPrioReceiver {
+                q: self,
+                phantom: PhantomData
+            }
+        )
+    }
+}
+
+//
+
+/// Priority message sending channel
+/// * Cloneable so that multiple tasks send messages to the same queue.
+pub struct MTPrioSender<'a, 'q, K, M>
+{
+    q: *mut MTPrioQueue<'a, K, M>,
+    phantom: PhantomData<&'q ()>
+}
+
+unsafe impl<K: Send, M: Send> Send for MTPrioSender<'_, '_, K, M> {}
+
+impl<K, M> Clone for MTPrioSender<'_, '_, K, M>
+{
+    fn clone(&self) -> Self
+    {
+        MTPrioSender {
+            q: self.q,
+            phantom: PhantomData
+        }
+    }
+}
+
+impl<K: Ord + Copy, M> MTPrioSender<'_, '_, K, M>
+{
+    /// Gets if there is a vacant message entry.
+    /// * Returns the number of vacant message entries.
+    pub fn vacant(&self) -> usize
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        q.mem.len() - q.msg_cnt.cnt()
+    }
+
+    /// Sends a message.
+    /// * `key` - priority key of the message. A smaller key is received earlier, and the same keys in order of sending.
+    /// * `msg` - the message to be sent.
+    /// * Blocks if there is no vacant message entry.
+    pub fn send(&mut self, key: K, mut msg: M)
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        loop {
+            match self.push(key, msg) {
+                Ok(()) => return,
+                Err(m) => msg = m
+            }
+
+            Minimult::wait(&q.msg_cnt, MTEventCond::LessThan(q.mem.len()));
+        }
+    }
+
+    /// Sends a message with a timeout.
+    /// * `key` - priority key of the message. A smaller key is received earlier, and the same keys in order of sending.
+    /// * `msg` - the message to be sent.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Blocks if there is no vacant message entry.
+    /// * Returns `Err(MTTimeout(msg))` giving back the message if no entry gets vacant within the timeout.
+    pub fn send_timeout(&mut self, key: K, mut msg: M, ticks: usize) -> Result<(), MTTimeout<M>>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            match self.push(key, msg) {
+                Ok(()) => return Ok(()),
+                Err(m) => msg = m
+            }
+
+            if !Minimult::wait_timeout(&q.msg_cnt, MTEventCond::LessThan(q.mem.len()), from, ticks) {
+                return Err(MTTimeout(msg));
+            }
+        }
+    }
+
+    /// Tries to send a message.
+    /// * `key` - priority key of the message. A smaller key is received earlier, and the same keys in order of sending.
+    /// * `msg` - the message to be sent.
+    /// * Returns `Err(msg)` giving back the message if there is no vacant message entry.
+    /// * Can be called from interrupt handlers.
+    pub fn try_send(&mut self, key: K, msg: M) -> Result<(), M>
+    {
+        self.push(key, msg)
+    }
+
+    fn push(&self, key: K, msg: M) -> Result<(), M>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        // NOTE: exclusive among senders, a receiver and interrupts
+        let r = cortex_m::interrupt::free(|_| {
+            let seq = q.seq;
+
+            if let Some(idx) = q.tree.flist_h_to_bheap(Some(MTPrioSeqKey {key, seq})) {
+                q.mem.write_volatile(idx, msg);
+
+                q.seq = seq.wrapping_add(1); // NOTE: wrapping-around is intended
+
+                q.msg_cnt.incr();
+
+                Ok(())
+            }
+            else {
+                Err(msg)
+            }
+        });
+
+        if r.is_ok() {
+            Minimult::signal(&q.msg_cnt);
+        }
+
+        r
+    }
+}
+
+//
+
+/// Priority message receiving channel
+pub struct MTPrioReceiver<'a, 'q, K, M>
+{
+    q: *mut MTPrioQueue<'a, K, M>,
+    phantom: PhantomData<&'q ()>
+}
+
+unsafe impl<K: Send, M: Send> Send for MTPrioReceiver<'_, '_, K, M> {}
+
+impl<K: Ord + Copy, M> MTPrioReceiver<'_, '_, K, M>
+{
+    /// Gets if there is an available message entry.
+    /// * Returns the number of available message entries.
+    pub fn available(&self) -> usize
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        q.msg_cnt.cnt()
+    }
+
+    /// Receives a message.
+    /// * Returns the received message with the smallest key.
+    /// * Blocks if there is no available message entry.
+    pub fn receive(&mut self) -> M
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        loop {
+            if let Some(msg) = self.try_receive() {
+                return msg;
+            }
+
+            Minimult::wait(&q.msg_cnt, MTEventCond::GreaterThan(0));
+        }
+    }
+
+    /// Receives a message with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the received message with the smallest key.
+    /// * Blocks if there is no available message entry.
+    /// * Returns `Err(MTTimeout)` if no message gets available within the timeout.
+    pub fn receive_timeout(&mut self, ticks: usize) -> Result<M, MTTimeout>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if let Some(msg) = self.try_receive() {
+                return Ok(msg);
+            }
+
+            if !Minimult::wait_timeout(&q.msg_cnt, MTEventCond::GreaterThan(0), from, ticks) {
+                return Err(MTTimeout(()));
+            }
+        }
+    }
+
+    /// Tries to receive a message.
+    /// * Returns the received message with the smallest key in `Option`.
+    /// * Gets `None` if there is no available message entry.
+    /// * Can be called from interrupt handlers.
+    pub fn try_receive(&mut self) -> Option<M>
+    {
+        let q = unsafe { self.q.as_mut().bk_unwrap() };
+
+        let msg = cortex_m::interrupt::free(|_| {
+            let idx = q.tree.bheap_h()?;
+
+            let msg = q.mem.read_volatile(idx);
+
+            q.tree.bheap_h_to_flist_h();
+
+            q.msg_cnt.decr();
+
+            Some(msg)
+        });
+
+        if msg.is_some() {
+            Minimult::signal(&q.msg_cnt);
+        }
+
+        msg
+    }
+}
@@ -0,0 +1,208 @@
+use core::marker::PhantomData;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+//
+
+const EMPTY: usize = 0;
+const OFFERED: usize = 1;
+const TAKEN: usize = 2;
+
+/// Zero-capacity channel where a sender and a receiver meet
+pub struct MTRendezvous<'a, M>
+{
+    slot: Option<M>,
+    state: MTEvent,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a, M> MTRendezvous<'a, M>
+{
+    pub(crate) fn new() -> MTRendezvous<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTRendezvous {
+            slot: None,
+            state: MTEvent::new(EMPTY),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets sending and receving channels.
+    /// * Returns a tuple of the sender and receiver pair.
+    pub fn ch<'r>(&'r mut self) -> (MTRendezvousSender<'a, 'r, M>, MTRendezvousReceiver<'a, 'r, M>)
+    {
+        (
+            MTRendezvousSender {
+                r: self,
+                phantom: PhantomData
+            },
+            MTRendezvousReceiver {
+                r: self,
+                phantom: PhantomData
+            }
+        )
+    }
+}
+
+//
+
+/// Rendezvous sending channel
+pub struct MTRendezvousSender<'a, 'r, M>
+{
+    r: *mut MTRendezvous<'a, M>,
+    phantom: PhantomData<&'r ()>
+}
+
+unsafe impl<M: Send> Send for MTRendezvousSender<'_, '_, M> {}
+
+impl<M> MTRendezvousSender<'_, '_, M>
+{
+    fn offer(&mut self, msg: M)
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| {
+            r.slot = Some(msg);
+            r.state.incr(); // EMPTY => OFFERED
+        });
+
+        Minimult::signal(&r.state);
+    }
+
+    fn complete(&mut self)
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        r.state.sub(TAKEN); // TAKEN => EMPTY
+    }
+
+    /// Sends a message.
+    /// * `msg` - the message to be sent.
+    /// * Blocks until a receiver takes the message.
+    pub fn send(&mut self, msg: M)
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        self.offer(msg);
+
+        while r.state.cnt() != TAKEN {
+            Minimult::wait(&r.state, MTEventCond::Equal(TAKEN));
+        }
+
+        self.complete();
+    }
+
+    /// Sends a message with a timeout.
+    /// * `msg` - the message to be sent.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Blocks until a receiver takes the message.
+    /// * Returns `Err(MTTimeout(msg))` giving back the message if no receiver takes it within the timeout.
+    pub fn send_timeout(&mut self, msg: M, ticks: usize) -> Result<(), MTTimeout<M>>
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        self.offer(msg);
+
+        while r.state.cnt() != TAKEN {
+            if !Minimult::wait_timeout(&r.state, MTEventCond::Equal(TAKEN), from, ticks) {
+                let msg = cortex_m::interrupt::free(|_| {
+                    if r.state.cnt() == OFFERED {
+                        r.state.decr(); // OFFERED => EMPTY
+                        r.slot.take()
+                    }
+                    else {
+                        None
+                    }
+                });
+
+                if let Some(msg) = msg {
+                    return Err(MTTimeout(msg));
+                }
+            }
+        }
+
+        self.complete();
+
+        Ok(())
+    }
+}
+
+//
+
+/// Rendezvous receiving channel
+pub struct MTRendezvousReceiver<'a, 'r, M>
+{
+    r: *mut MTRendezvous<'a, M>,
+    phantom: PhantomData<&'r ()>
+}
+
+unsafe impl<M: Send> Send for MTRendezvousReceiver<'_, '_, M> {}
+
+impl<M> MTRendezvousReceiver<'_, '_, M>
+{
+    /// Receives a message.
+    /// * Returns the received message.
+    /// * Blocks until a sender offers a message, then releases the sender.
+    pub fn receive(&mut self) -> M
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        loop {
+            if let Some(msg) = self.try_receive() {
+                return msg;
+            }
+
+            Minimult::wait(&r.state, MTEventCond::Equal(OFFERED));
+        }
+    }
+
+    /// Receives a message with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the received message.
+    /// * Blocks until a sender offers a message, then releases the sender.
+    /// * Returns `Err(MTTimeout)` if no message is offered within the timeout.
+    pub fn receive_timeout(&mut self, ticks: usize) -> Result<M, MTTimeout>
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if let Some(msg) = self.try_receive() {
+                return Ok(msg);
+            }
+
+            if !Minimult::wait_timeout(&r.state, MTEventCond::Equal(OFFERED), from, ticks) {
+                return Err(MTTimeout(()));
+            }
+        }
+    }
+
+    /// Tries to receive a message.
+    /// * Returns the received message in `Option`, releasing the sender.
+    /// * Gets `None` if no sender is offering a message.
+    pub fn try_receive(&mut self) -> Option<M>
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        let msg = cortex_m::interrupt::free(|_| {
+            if r.state.cnt() == OFFERED {
+                r.state.incr(); // OFFERED => TAKEN
+                r.slot.take()
+            }
+            else {
+                None
+            }
+        });
+
+        if msg.is_some() {
+            Minimult::signal(&r.state);
+        }
+
+        msg
+    }
+}
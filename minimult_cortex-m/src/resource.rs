@@ -0,0 +1,124 @@
+use core::marker::PhantomData;
+
+use crate::MTTaskPri;
+use crate::minimult::Minimult;
+use crate::kernel::{MTCeilingCore, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Resource locked with the immediate priority ceiling protocol
+pub struct MTResource<'a, M>
+{
+    holder: M,
+    core: MTCeilingCore,
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a, M> MTResource<'a, M>
+{
+    pub(crate) fn new(ceiling: MTTaskPri, holder: M) -> MTResource<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTResource {
+            holder,
+            core: MTCeilingCore::new(ceiling),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets a resource access channel.
+    /// * Returns the resource access channel.
+    pub fn ch<'s>(&'s self) -> MTResourceCh<'a, 's, M>
+    {
+        MTResourceCh {
+            r: (self as *const Self) as *mut Self, // NOTE: mutability conversion
+            phantom: PhantomData
+        }
+    }
+}
+
+//
+
+/// Resource access channel
+pub struct MTResourceCh<'a, 's, M>
+{
+    r: *mut MTResource<'a, M>,
+    phantom: PhantomData<&'s ()>
+}
+
+unsafe impl<M: Send> Send for MTResourceCh<'_, '_, M> {}
+
+impl<M> MTResourceCh<'_, '_, M>
+{
+    /// Locks a resource.
+    /// * Returns a `DerefMut`-able wrapper of the variable in the resource.
+    /// * A running task is raised to the ceiling priority until unlocked.
+    ///   The task priority must not be higher than the ceiling.
+    /// * Nested locks may be unlocked in any order;
+    ///   the task stays at the highest ceiling of the resources still locked.
+    /// * Blocks if the resource is locked by other channels,
+    ///   which happens only for tasks of the same priority as the ceiling.
+    pub fn lock<'c>(&'c self) -> MTResourceGuard<'c, M>
+    {
+        loop {
+            if let Some(v) = self.try_lock() {
+                return v;
+            }
+            else {
+                let r = unsafe { self.r.as_mut().bk_unwrap() };
+                Minimult::wait(r.core.lock_ev(), MTEventCond::Equal(0));
+            }
+        }
+    }
+
+    /// Tries to lock a resource.
+    /// * Returns a `DerefMut`-able wrapper of the variable in the resource in `Option`.
+    /// * Gets `None` if the resource is locked by other channels.
+    pub fn try_lock<'c>(&'c self) -> Option<MTResourceGuard<'c, M>>
+    {
+        let r = unsafe { self.r.as_mut().bk_unwrap() };
+
+        if Minimult::ceiling_lock(&mut r.core) {
+            Some(MTResourceGuard {
+                holder: &mut r.holder,
+                core: &mut r.core
+            })
+        }
+        else {
+            None
+        }
+    }
+}
+
+//
+
+/// Resource's lock scope wrapper
+pub struct MTResourceGuard<'c, M>
+{
+    holder: &'c mut M,
+    core: &'c mut MTCeilingCore
+}
+
+impl<M> core::ops::Deref for MTResourceGuard<'_, M>
+{
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target
+    {
+        self.holder
+    }
+}
+
+impl<M> core::ops::DerefMut for MTResourceGuard<'_, M>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        self.holder
+    }
+}
+
+impl<M> Drop for MTResourceGuard<'_, M>
+{
+    fn drop(&mut self)
+    {
+        Minimult::ceiling_unlock(self.core);
+    }
+}
@@ -0,0 +1,121 @@
+use core::marker::PhantomData;
+use core::ptr::null;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::msgqueue::MTMsgReceiver;
+use crate::shared::MTSharedCh;
+use crate::kernel::{MTEventWait, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Source which gets ready in `MTSelect`
+#[derive(Debug, PartialEq)]
+pub enum MTSelected
+{
+    /// Index of the source returned when added.
+    Source(usize),
+    /// Kicked by other tasks/interrupts.
+    Kicked
+}
+
+//
+
+/// Selection to wait for any of multiple sources
+/// * `N` - maximum number of the sources except a kick.
+pub struct MTSelect<'s, const N: usize>
+{
+    evs: [MTEventWait; N],
+    num: usize,
+    kick: bool,
+    phantom: PhantomData<&'s ()>
+}
+
+impl<const N: usize> Default for MTSelect<'_, N>
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<'s, const N: usize> MTSelect<'s, N>
+{
+    /// Creates an empty selection.
+    /// * Returns the created selection, to which sources are added.
+    pub fn new() -> MTSelect<'s, N>
+    {
+        MTSelect {
+            evs: [MTEventWait {ev: null(), cond: MTEventCond::None}; N],
+            num: 0,
+            kick: false,
+            phantom: PhantomData
+        }
+    }
+
+    fn add(&mut self, w: MTEventWait) -> usize
+    {
+        assert!(self.num < N,
+                "select: sources over capacity {}", N);
+
+        let i = self.num;
+
+        self.evs[i] = w;
+        self.num += 1;
+
+        i
+    }
+
+    /// Adds a message receiving channel.
+    /// * `rcv` - the receiver which gets ready when a message is available.
+    /// * Returns the index of the source.
+    pub fn receiver<M>(&mut self, rcv: &'s MTMsgReceiver<'_, '_, M>) -> usize
+    {
+        self.add(rcv.available_wait())
+    }
+
+    /// Adds a shared variable access channel.
+    /// * `ch` - the channel which gets ready when the shared variable is neither `look`ed nor `touch`ed.
+    /// * Returns the index of the source.
+    pub fn shared<M>(&mut self, ch: &'s MTSharedCh<'_, '_, M>) -> usize
+    {
+        self.add(ch.free_wait())
+    }
+
+    /// Adds a kick to a current running task.
+    /// * The selection gets `MTSelected::Kicked` when kicked, as `Minimult::idle` does.
+    pub fn kick(&mut self)
+    {
+        self.kick = true;
+    }
+
+    /// Waits for any of the sources.
+    /// * Returns the source which is ready, checking the sources in order of addition and the kick last.
+    /// * Blocks until any of the sources gets ready.
+    pub fn wait(&self) -> MTSelected
+    {
+        let r = Minimult::select(&self.evs[..self.num], self.kick, None).bk_unwrap();
+
+        Self::selected(r)
+    }
+
+    /// Waits for any of the sources with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the source which is ready, checking the sources in order of addition and the kick last.
+    /// * Blocks until any of the sources gets ready.
+    /// * Returns `Err(MTTimeout)` if none of the sources gets ready within the timeout.
+    pub fn wait_timeout(&self, ticks: usize) -> Result<MTSelected, MTTimeout>
+    {
+        let r = Minimult::select(&self.evs[..self.num], self.kick, Some(ticks)).ok_or(MTTimeout(()))?;
+
+        Ok(Self::selected(r))
+    }
+
+    fn selected(r: Option<usize>) -> MTSelected
+    {
+        if let Some(i) = r {
+            MTSelected::Source(i)
+        }
+        else {
+            MTSelected::Kicked
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use core::marker::PhantomData;
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Counting semaphore, or binary semaphore if the maximum count is 1
+pub struct MTSemaphore<'a>
+{
+    cnt: MTEvent,
+    max: usize,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl Sync for MTSemaphore<'_> {}
+
+impl<'a> MTSemaphore<'a>
+{
+    pub(crate) fn new(init: usize, max: usize) -> MTSemaphore<'a> // NOTE: lifetime safety correctness
+    {
+        assert!(init <= max,
+                "semaphore: initial count {} over maximum {}", init, max);
+
+        MTSemaphore {
+            cnt: MTEvent::new(init),
+            max,
+            phantom: PhantomData
+        }
+    }
+}
+
+impl MTSemaphore<'_>
+{
+    /// Gets a semaphore count.
+    /// * Returns the number of times which can be acquired without blocking.
+    pub fn count(&self) -> usize
+    {
+        self.cnt.cnt()
+    }
+
+    /// Acquires a semaphore.
+    /// * Decrements the count.
+    /// * Blocks if the count is zero.
+    pub fn acquire(&self)
+    {
+        loop {
+            if self.try_acquire() {
+                break;
+            }
+
+            Minimult::wait(&self.cnt, MTEventCond::GreaterThan(0));
+        }
+    }
+
+    /// Acquires a semaphore with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Decrements the count.
+    /// * Blocks if the count is zero.
+    /// * Returns `Err(MTTimeout)` if the count doesn't get non-zero within the timeout.
+    pub fn acquire_timeout(&self, ticks: usize) -> Result<(), MTTimeout>
+    {
+        let from = Minimult::tick_count();
+
+        loop {
+            if self.try_acquire() {
+                return Ok(());
+            }
+
+            if !Minimult::wait_timeout(&self.cnt, MTEventCond::GreaterThan(0), from, ticks) {
+                return Err(MTTimeout(()));
+            }
+        }
+    }
+
+    /// Tries to acquire a semaphore.
+    /// * Decrements the count.
+    /// * Returns `false` if the count is zero.
+    pub fn try_acquire(&self) -> bool
+    {
+        let cnt = (&self.cnt as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let cnt = unsafe { cnt.as_mut().bk_unwrap() };
+
+        cnt.decr_ifgt0()
+    }
+
+    /// Releases a semaphore.
+    /// * Increments the count and wakes up a task waiting to acquire.
+    /// * Returns `false` if the count is already maximum.
+    /// * Can be called from interrupt handlers.
+    pub fn release(&self) -> bool
+    {
+        let cnt = (&self.cnt as *const MTEvent) as *mut MTEvent; // NOTE: mutability conversion
+        let cnt = unsafe { cnt.as_mut().bk_unwrap() };
+
+        if cnt.incr_iflt(self.max) {
+            Minimult::signal(cnt);
+            true
+        }
+        else {
+            false
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use core::marker::PhantomData;
 
-use crate::minimult::Minimult;
-use crate::kernel::{MTEvent, MTEventCond};
+use crate::minimult::{Minimult, MTTimeout};
+use crate::kernel::{MTEvent, MTEventCond, MTEventWait};
 use crate::bkptpanic::BKUnwrap;
 
 /// Shared variable among tasks
@@ -63,6 +63,28 @@ impl<M> MTSharedCh<'_, '_, M>
         }
     }
 
+    /// Makes an immutable access to a shared variable with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns a `Deref`-able wrapper of the shared variable.
+    /// * Blocks if the shared variable is `touch`ed by other channels.
+    /// * Returns `Err(MTTimeout)` if the shared variable doesn't get accessible within the timeout.
+    pub fn look_timeout<'c>(&'c self, ticks: usize) -> Result<MTSharedLook<'c, M>, MTTimeout>
+    {
+        let from = Minimult::tick_count();
+
+        loop {
+            if let Some(v) = self.try_look() {
+                return Ok(v);
+            }
+            else {
+                let s = unsafe { self.s.as_mut().bk_unwrap() };
+                if !Minimult::wait_timeout(&s.rw_cnt, MTEventCond::GreaterThan(0), from, ticks) {
+                    return Err(MTTimeout(()));
+                }
+            }
+        }
+    }
+
     /// Tries to make an immutable access to a shared variable.
     /// * Returns a `Deref`-able wrapper of the shared variable in `Option`.
     /// * Gets `None` if the shared variable is `touch`ed by other channels.
@@ -97,6 +119,28 @@ impl<M> MTSharedCh<'_, '_, M>
         }
     }
 
+    /// Makes a mutable access to a shared variable with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns a `DerefMut`-able wrapper of the shared variable.
+    /// * Blocks if the shared variable is `look`ed or `touch`ed by other channels.
+    /// * Returns `Err(MTTimeout)` if the shared variable doesn't get accessible within the timeout.
+    pub fn touch_timeout<'c>(&'c self, ticks: usize) -> Result<MTSharedTouch<'c, M>, MTTimeout>
+    {
+        let from = Minimult::tick_count();
+
+        loop {
+            if let Some(v) = self.try_touch() {
+                return Ok(v);
+            }
+            else {
+                let s = unsafe { self.s.as_mut().bk_unwrap() };
+                if !Minimult::wait_timeout(&s.rw_cnt, MTEventCond::Equal(1), from, ticks) {
+                    return Err(MTTimeout(()));
+                }
+            }
+        }
+    }
+
     /// Tries to make a mutable access to a shared variable.
     /// * Returns a `DerefMut`-able wrapper of the shared variable in `Option`.
     /// * Gets `None` if the shared variable is `look`ed or `touch`ed by other channels.
@@ -114,6 +158,26 @@ impl<M> MTSharedCh<'_, '_, M>
             None
         }
     }
+
+    pub(crate) fn free_wait(&self) -> MTEventWait
+    {
+        let s = unsafe { self.s.as_mut().bk_unwrap() };
+
+        MTEventWait {
+            ev: &s.rw_cnt,
+            cond: MTEventCond::Equal(1)
+        }
+    }
+}
+
+//
+
+/// Shared variable's access scope wrapper which can be released and reacquired, e.g. by `MTCondvar`
+pub trait MTSharedGuard: Sized
+{
+    #[doc(hidden)]
+    fn relock<F, A>(self, blocking: F) -> (Self, A)
+    where F: FnOnce() -> A;
 }
 
 //
@@ -135,6 +199,32 @@ impl<M> core::ops::Deref for MTSharedLook<'_, M>
     }
 }
 
+impl<M> MTSharedGuard for MTSharedLook<'_, M>
+{
+    fn relock<F, A>(self, blocking: F) -> (Self, A)
+    where F: FnOnce() -> A
+    {
+        let holder: *const M = self.holder;
+        let rw_cnt: *mut MTEvent = &mut *self.rw_cnt;
+        drop(self);
+
+        let r = blocking();
+
+        let rw_cnt = unsafe { rw_cnt.as_mut().bk_unwrap() };
+
+        while !rw_cnt.incr_ifgt0() {
+            Minimult::wait(rw_cnt, MTEventCond::GreaterThan(0));
+        }
+
+        let v = MTSharedLook {
+            holder: unsafe { holder.as_ref().bk_unwrap() },
+            rw_cnt
+        };
+
+        (v, r)
+    }
+}
+
 impl<M> Drop for MTSharedLook<'_, M>
 {
     fn drop(&mut self)
@@ -171,6 +261,32 @@ impl<M> core::ops::DerefMut for MTSharedTouch<'_, M>
     }
 }
 
+impl<M> MTSharedGuard for MTSharedTouch<'_, M>
+{
+    fn relock<F, A>(self, blocking: F) -> (Self, A)
+    where F: FnOnce() -> A
+    {
+        let holder: *mut M = &mut *self.holder;
+        let rw_cnt: *mut MTEvent = &mut *self.rw_cnt;
+        drop(self);
+
+        let r = blocking();
+
+        let rw_cnt = unsafe { rw_cnt.as_mut().bk_unwrap() };
+
+        while !rw_cnt.decr_if1() {
+            Minimult::wait(rw_cnt, MTEventCond::Equal(1));
+        }
+
+        let v = MTSharedTouch {
+            holder: unsafe { holder.as_mut().bk_unwrap() },
+            rw_cnt
+        };
+
+        (v, r)
+    }
+}
+
 impl<M> Drop for MTSharedTouch<'_, M>
 {
     fn drop(&mut self)
@@ -0,0 +1,253 @@
+use core::marker::PhantomData;
+use core::mem::{MaybeUninit, size_of};
+
+use crate::{MTTaskId, MTTaskPri};
+use crate::minimult::Minimult;
+use crate::memory::MTRawArray;
+use crate::kernel::{mtkernel_get_mut, MTEvent, MTEventCond, align_down};
+use crate::bkptpanic::BKUnwrap;
+
+//
+
+struct MTJoinCell<V>
+{
+    exit_ev: MTEvent,
+    ret_set: bool,
+    detached: bool,
+    ret: MaybeUninit<V>
+}
+
+struct MTJoinCellPtr<V>(*mut MTJoinCell<V>);
+
+unsafe impl<V: Send> Send for MTJoinCellPtr<V> {}
+
+fn join_cell<V>(mem: &MTRawArray<usize>) -> (*mut MTJoinCell<V>, *mut usize)
+{
+    // join cell at the stack tail
+
+    let sp_end = mem.tail() as usize;
+    let sp_end = align_down::<MTJoinCell<V>>(sp_end - size_of::<MTJoinCell<V>>());
+
+    assert!(sp_end >= mem.head() as usize,
+            "{} bytes shortage of stack for a return value", size_of::<MTJoinCell<V>>());
+
+    (sp_end as *mut MTJoinCell<V>, sp_end as *mut usize)
+}
+
+fn join_task<T, V>(cell: *mut MTJoinCell<V>, task: T) -> impl FnOnce() + Send
+where T: FnOnce() -> V + Send, V: Send
+{
+    unsafe {
+        cell.write(MTJoinCell {
+            exit_ev: MTEvent::new(0),
+            ret_set: false,
+            detached: false,
+            ret: MaybeUninit::uninit()
+        });
+    }
+
+    let cell_ptr = MTJoinCellPtr(cell);
+
+    move || {
+        let ret = task();
+
+        let cell = unsafe { cell_ptr.0.as_mut().bk_unwrap() };
+
+        let ret = cortex_m::interrupt::free(|_| {
+            if cell.detached {
+                Some(ret) // nobody joins
+            }
+            else {
+                cell.ret = MaybeUninit::new(ret);
+                cell.ret_set = true;
+                None
+            }
+        });
+
+        drop(ret);
+    }
+}
+
+//
+
+/// Stack reserved in advance to spawn a task dynamically
+pub struct MTStack<'a>
+{
+    mem: MTRawArray<usize>,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl Send for MTStack<'_> {}
+
+impl<'a> MTStack<'a>
+{
+    pub(crate) fn new(mem: MTRawArray<usize>) -> MTStack<'a> // NOTE: lifetime safety correctness
+    {
+        MTStack {
+            mem,
+            phantom: PhantomData
+        }
+    }
+
+    fn spawn_once<T, V>(&mut self, tid: Option<MTTaskId>, pri: MTTaskPri, task: T) -> Option<MTJoinHandle<'_, V>>
+    where T: FnOnce() -> V + Send + 'a, V: Send + 'a
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        let sp_start = self.mem.head();
+        let (cell, sp_end) = join_cell::<V>(&self.mem);
+
+        if tm.stack_in_use(sp_start) {
+            return None;
+        }
+
+        let t = join_task(cell, task);
+
+        let exit_ev = unsafe { &mut (*cell).exit_ev };
+
+        let tid = tm.spawn_once(tid, pri, sp_start, sp_end, exit_ev, t)?;
+
+        Some(MTJoinHandle {
+            tid,
+            cell,
+            phantom: PhantomData
+        })
+    }
+
+    /// Spawns a closure as a task on this stack.
+    /// * `tid` - task identifier. `0` to `num_tasks - 1`.
+    /// * `pri` - task priority. The lower value is the higher priority.
+    /// * `task: T` - task closure, which returns a value of `V`.
+    /// * Returns a join handle of the task in `Option`.
+    /// * Gets `None` if the task `tid` is not finished yet,
+    ///   or a task previously spawned on this stack is not finished yet.
+    /// * The return value is kept at the tail of this stack.
+    pub fn spawn<T, V>(&mut self, tid: MTTaskId, pri: MTTaskPri, task: T) -> Option<MTJoinHandle<'_, V>>
+    where T: FnOnce() -> V + Send + 'a, V: Send + 'a // NOTE: lifetime safety correctness
+    {
+        self.spawn_once(Some(tid), pri, task)
+    }
+
+    /// Spawns a closure as a task on this stack, with any task identifier not in use.
+    /// * `pri` - task priority. The lower value is the higher priority.
+    /// * `task: T` - task closure, which returns a value of `V`.
+    /// * Returns a join handle of the task in `Option`.
+    /// * Gets `None` if every task is not finished yet,
+    ///   or a task previously spawned on this stack is not finished yet.
+    /// * The return value is kept at the tail of this stack.
+    pub fn spawn_any<T, V>(&mut self, pri: MTTaskPri, task: T) -> Option<MTJoinHandle<'_, V>>
+    where T: FnOnce() -> V + Send + 'a, V: Send + 'a // NOTE: lifetime safety correctness
+    {
+        self.spawn_once(None, pri, task)
+    }
+}
+
+//
+
+/// Handle to join a spawned or registered task
+/// * Dropping a handle without joining drops the return value, even after the task finishes.
+pub struct MTJoinHandle<'s, V>
+{
+    tid: MTTaskId,
+    cell: *mut MTJoinCell<V>,
+    phantom: PhantomData<&'s mut ()>
+}
+
+unsafe impl<V: Send> Send for MTJoinHandle<'_, V> {}
+
+impl<'a, V> MTJoinHandle<'a, V>
+{
+    pub(crate) fn register<T>(tid: MTTaskId, pri: MTTaskPri, mem: MTRawArray<usize>, task: T) -> MTJoinHandle<'a, V> // NOTE: lifetime safety correctness
+    where T: FnOnce() -> V + Send + 'a, V: Send + 'a
+    {
+        let tm = mtkernel_get_mut().bk_unwrap();
+
+        let (cell, sp_end) = join_cell::<V>(&mem);
+
+        let t = join_task(cell, task);
+
+        let exit_ev = unsafe { &mut (*cell).exit_ev };
+
+        tm.register_once(tid, pri, mem.head(), sp_end, exit_ev, t);
+
+        MTJoinHandle {
+            tid,
+            cell,
+            phantom: PhantomData
+        }
+    }
+}
+
+impl<V> MTJoinHandle<'_, V>
+{
+    /// Gets task identifier of a spawned task.
+    /// * Returns the task identifier.
+    pub fn tid(&self) -> MTTaskId
+    {
+        self.tid
+    }
+
+    /// Gets if a spawned task has finished.
+    /// * Returns `true` if finished.
+    pub fn is_finished(&self) -> bool
+    {
+        let cell = unsafe { self.cell.as_ref().bk_unwrap() };
+
+        cell.exit_ev.cnt() > 0
+    }
+
+    /// Joins a spawned task.
+    /// * Returns the return value of the task.
+    /// * Blocks until the task finishes.
+    pub fn join(self) -> V
+    {
+        let cell = unsafe { self.cell.as_mut().bk_unwrap() };
+
+        loop {
+            if cell.exit_ev.cnt() > 0 {
+                break;
+            }
+
+            Minimult::wait(&cell.exit_ev, MTEventCond::GreaterThan(0));
+        }
+
+        cell.ret_set = false; // NOTE: taken out, not dropped
+
+        unsafe { cell.ret.as_ptr().read() }
+    }
+
+    /// Tries to join a spawned task.
+    /// * Returns the return value of the task in `Result`.
+    /// * Gets `Err` giving back the handle if the task is not finished yet.
+    pub fn try_join(self) -> Result<V, Self>
+    {
+        if self.is_finished() {
+            Ok(self.join())
+        }
+        else {
+            Err(self)
+        }
+    }
+}
+
+impl<V> Drop for MTJoinHandle<'_, V>
+{
+    fn drop(&mut self)
+    {
+        let cell = unsafe { self.cell.as_mut().bk_unwrap() };
+
+        let ret = cortex_m::interrupt::free(|_| {
+            cell.detached = true; // the task drops its return value if not finished yet
+
+            if cell.ret_set {
+                cell.ret_set = false;
+                Some(unsafe { cell.ret.as_ptr().read() })
+            }
+            else {
+                None
+            }
+        });
+
+        drop(ret);
+    }
+}
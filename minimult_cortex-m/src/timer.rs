@@ -0,0 +1,324 @@
+use core::marker::PhantomData;
+use core::ptr::{null_mut, addr_of, addr_of_mut};
+
+use crate::minimult::Minimult;
+use crate::memory::MTRawArray;
+use crate::kernel::{MTEvent, MTEventCond, tick_elapsed};
+use crate::bkptpanic::BKUnwrap;
+
+//
+
+static mut O_MTTIMERSVC: Option<MTTimerSvc> = None;
+
+pub(crate) fn mttimersvc_create()
+{
+    unsafe {
+        assert!((*addr_of!(O_MTTIMERSVC)).is_none(),
+                "timer service: double registration");
+
+        O_MTTIMERSVC = Some(MTTimerSvc {
+            head: null_mut(),
+            cmd_ev: MTEvent::new(0)
+        });
+    }
+}
+
+pub(crate) fn mttimersvc_exists() -> bool
+{
+    unsafe {
+        (*addr_of!(O_MTTIMERSVC)).is_some()
+    }
+}
+
+fn mttimersvc_get_mut() -> Option<&'static mut MTTimerSvc>
+{
+    unsafe {
+        (*addr_of_mut!(O_MTTIMERSVC)).as_mut()
+    }
+}
+
+//
+
+pub(crate) struct MTTimerCtl
+{
+    next: *mut MTTimerCtl,
+    linked: bool,
+    from: usize,
+    ticks: usize,
+    periodic: bool,
+    started: bool,
+    busy_ev: MTEvent,
+    func: *mut dyn FnMut()
+}
+
+impl MTTimerCtl
+{
+    fn remaining(&self, now: usize) -> isize
+    {
+        // negative if overdue
+        self.from.wrapping_add(self.ticks).wrapping_sub(now) as isize
+    }
+}
+
+//
+
+struct MTTimerSvc
+{
+    head: *mut MTTimerCtl, // sorted in order of expiry
+    cmd_ev: MTEvent
+}
+
+impl MTTimerSvc
+{
+    fn link(&mut self, ctl: &mut MTTimerCtl, now: usize)
+    {
+        let key = ctl.remaining(now);
+
+        let mut pp: *mut *mut MTTimerCtl = &mut self.head;
+
+        unsafe {
+            while !(*pp).is_null() && ((**pp).remaining(now) <= key) {
+                pp = &mut (**pp).next;
+            }
+
+            ctl.next = *pp;
+            *pp = ctl;
+        }
+
+        ctl.linked = true;
+    }
+
+    fn unlink(&mut self, ctl: &mut MTTimerCtl)
+    {
+        if !ctl.linked {
+            return;
+        }
+
+        let target: *mut MTTimerCtl = ctl;
+        let mut pp: *mut *mut MTTimerCtl = &mut self.head;
+
+        unsafe {
+            while !(*pp).is_null() {
+                if *pp == target {
+                    *pp = ctl.next;
+                    break;
+                }
+                pp = &mut (**pp).next;
+            }
+        }
+
+        ctl.next = null_mut();
+        ctl.linked = false;
+    }
+
+    fn pop_expired(&mut self, now: usize) -> Option<*mut MTTimerCtl>
+    {
+        let ctl = unsafe { self.head.as_mut()? };
+
+        if !tick_elapsed(now, ctl.from, ctl.ticks) {
+            return None;
+        }
+
+        self.unlink(ctl);
+
+        if ctl.periodic {
+            ctl.from = ctl.from.wrapping_add(ctl.ticks); // no drift
+            self.link(ctl, now);
+        }
+
+        ctl.busy_ev.incr(); // until called back
+
+        Some(ctl)
+    }
+
+    fn next_expiry(&self) -> Option<(usize, usize)>
+    {
+        let ctl = unsafe { self.head.as_ref()? };
+
+        Some((ctl.from, ctl.ticks))
+    }
+
+    fn command(&mut self)
+    {
+        self.cmd_ev.incr();
+        Minimult::signal(&self.cmd_ev);
+    }
+}
+
+pub(crate) fn mttimersvc_loop()
+{
+    let svc = mttimersvc_get_mut().bk_unwrap();
+
+    loop {
+        let cmd_cnt = svc.cmd_ev.cnt();
+
+        // call back expired timers
+
+        loop {
+            let now = Minimult::tick_count();
+
+            let ctl = cortex_m::interrupt::free(|_| svc.pop_expired(now));
+
+            if let Some(ctl) = unsafe { ctl.and_then(|ctl| ctl.as_mut()) } {
+                unsafe { (*ctl.func)() };
+
+                ctl.busy_ev.decr();
+                Minimult::signal(&ctl.busy_ev);
+            }
+            else {
+                break;
+            }
+        }
+
+        // wait for the nearest expiry or a command
+
+        let next = cortex_m::interrupt::free(|_| svc.next_expiry());
+
+        if let Some((from, ticks)) = next {
+            Minimult::wait_timeout(&svc.cmd_ev, MTEventCond::NotEqual(cmd_cnt), from, ticks);
+        }
+        else {
+            Minimult::wait(&svc.cmd_ev, MTEventCond::NotEqual(cmd_cnt));
+        }
+    }
+}
+
+//
+
+/// Software timer which calls back a closure in a timer service task
+/// * Dropping a timer stops it and drops the closure,
+///   waiting for the closure being called back if any.
+pub struct MTTimer<'a>
+{
+    ctl: *mut MTTimerCtl,
+    phantom: PhantomData<&'a ()>
+}
+
+unsafe impl Send for MTTimer<'_> {}
+
+impl<'a> MTTimer<'a>
+{
+    pub(crate) fn new<F>(mem: MTRawArray<MTTimerCtl>, mem_f: MTRawArray<F>, f: F) -> MTTimer<'a> // NOTE: lifetime safety correctness
+    where F: FnMut() + Send + 'a
+    {
+        mem_f.write(0_usize, f);
+
+        let func: *mut (dyn FnMut() + 'a) = mem_f.head();
+        let func: *mut dyn FnMut() = unsafe { core::mem::transmute(func) }; // NOTE: unsafe lifetime
+
+        mem.write(0_usize, MTTimerCtl {
+            next: null_mut(),
+            linked: false,
+            from: 0,
+            ticks: 0,
+            periodic: false,
+            started: false,
+            busy_ev: MTEvent::new(0),
+            func
+        });
+
+        MTTimer {
+            ctl: mem.head(),
+            phantom: PhantomData
+        }
+    }
+}
+
+impl MTTimer<'_>
+{
+    fn restart(&mut self, ticks: Option<usize>, periodic: Option<bool>)
+    {
+        let svc = mttimersvc_get_mut().bk_unwrap();
+        let ctl = unsafe { self.ctl.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| {
+            let now = Minimult::tick_count();
+
+            svc.unlink(ctl);
+
+            ctl.from = now;
+            if let Some(ticks) = ticks {
+                ctl.ticks = ticks;
+            }
+            if let Some(periodic) = periodic {
+                ctl.periodic = periodic;
+            }
+            ctl.started = true;
+
+            svc.link(ctl, now);
+        });
+
+        svc.command();
+    }
+
+    /// Starts a one-shot timer.
+    /// * `ticks` - number of `Minimult::tick`s until the closure is called back.
+    /// * Restarts if the timer is already active.
+    pub fn start(&mut self, ticks: usize)
+    {
+        self.restart(Some(ticks), Some(false));
+    }
+
+    /// Starts a periodic timer.
+    /// * `ticks` - number of `Minimult::tick`s of the period to call back the closure.
+    /// * Restarts if the timer is already active.
+    pub fn start_periodic(&mut self, ticks: usize)
+    {
+        assert!(ticks > 0,
+                "timer: zero period");
+
+        self.restart(Some(ticks), Some(true));
+    }
+
+    /// Stops a timer.
+    /// * Nothing happens if the timer is not active.
+    pub fn stop(&mut self)
+    {
+        let svc = mttimersvc_get_mut().bk_unwrap();
+        let ctl = unsafe { self.ctl.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| svc.unlink(ctl));
+
+        svc.command();
+    }
+
+    /// Restarts a timer from now with the same number of ticks and mode as the last start.
+    /// * The timer must have been started before.
+    pub fn reset(&mut self)
+    {
+        let ctl = unsafe { self.ctl.as_ref().bk_unwrap() };
+
+        assert!(ctl.started,
+                "timer: reset before start");
+
+        self.restart(None, None);
+    }
+
+    /// Gets if a timer is active.
+    /// * Returns `true` if the timer is waiting for its expiry.
+    pub fn is_active(&self) -> bool
+    {
+        let ctl = unsafe { self.ctl.as_ref().bk_unwrap() };
+
+        ctl.linked
+    }
+}
+
+impl Drop for MTTimer<'_>
+{
+    fn drop(&mut self)
+    {
+        let svc = mttimersvc_get_mut().bk_unwrap();
+        let ctl = unsafe { self.ctl.as_mut().bk_unwrap() };
+
+        cortex_m::interrupt::free(|_| svc.unlink(ctl));
+
+        while ctl.busy_ev.cnt() != 0 {
+            Minimult::wait(&ctl.busy_ev, MTEventCond::Equal(0));
+        }
+
+        unsafe { core::ptr::drop_in_place(ctl.func) };
+
+        svc.command();
+    }
+}
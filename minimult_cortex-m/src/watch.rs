@@ -0,0 +1,175 @@
+use core::marker::PhantomData;
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::minimult::{Minimult, MTTimeout};
+use crate::kernel::{MTEvent, MTEventCond};
+use crate::bkptpanic::BKUnwrap;
+
+/// Latest-value channel where a value is overwritten without blocking
+pub struct MTWatch<'a, M>
+{
+    value: M,
+    version: MTEvent, // NOTE: odd while being written
+    phantom: PhantomData<&'a ()>
+}
+
+impl<'a, M: Copy> MTWatch<'a, M>
+{
+    pub(crate) fn new(init: M) -> MTWatch<'a, M> // NOTE: lifetime safety correctness
+    {
+        MTWatch {
+            value: init,
+            version: MTEvent::new(0),
+            phantom: PhantomData
+        }
+    }
+
+    /// Gets sending and receving channels.
+    /// * Returns a tuple of the sender and receiver pair.
+    /// * The receiver can be cloned for multiple consumers.
+    pub fn ch<'w>(&'w mut self) -> (MTWatchSender<'a, 'w, M>, MTWatchReceiver<'a, 'w, M>)
+    {
+        let seen = self.version.cnt();
+
+        (
+            MTWatchSender {
+                w: self,
+                phantom: PhantomData
+            },
+            MTWatchReceiver {
+                w: self,
+                seen,
+                phantom: PhantomData
+            }
+        )
+    }
+}
+
+//
+
+/// Watch sending channel
+pub struct MTWatchSender<'a, 'w, M>
+{
+    w: *mut MTWatch<'a, M>,
+    phantom: PhantomData<&'w ()>
+}
+
+unsafe impl<M: Send> Send for MTWatchSender<'_, '_, M> {}
+
+impl<M: Copy> MTWatchSender<'_, '_, M>
+{
+    /// Sends a value.
+    /// * `value` - the value which overwrites the previous one.
+    /// * Never blocks even if receivers have not received the previous one.
+    /// * Can be called from interrupt handlers.
+    pub fn send(&mut self, value: M)
+    {
+        let w = unsafe { self.w.as_mut().bk_unwrap() };
+
+        w.version.incr(); // to odd
+        unsafe { write_volatile(&mut w.value, value) };
+        w.version.incr(); // to even
+
+        Minimult::signal(&w.version);
+    }
+}
+
+//
+
+/// Watch receiving channel
+pub struct MTWatchReceiver<'a, 'w, M>
+{
+    w: *mut MTWatch<'a, M>,
+    seen: usize,
+    phantom: PhantomData<&'w ()>
+}
+
+unsafe impl<M: Send> Send for MTWatchReceiver<'_, '_, M> {}
+
+impl<M> Clone for MTWatchReceiver<'_, '_, M>
+{
+    fn clone(&self) -> Self
+    {
+        MTWatchReceiver {
+            w: self.w,
+            seen: self.seen,
+            phantom: PhantomData
+        }
+    }
+}
+
+impl<M: Copy> MTWatchReceiver<'_, '_, M>
+{
+    fn read(&mut self) -> M
+    {
+        let w = unsafe { self.w.as_mut().bk_unwrap() };
+
+        loop {
+            let ver0 = w.version.cnt();
+
+            if ver0 % 2 != 0 {
+                Minimult::wait(&w.version, MTEventCond::NotEqual(ver0));
+                continue;
+            }
+
+            let value = unsafe { read_volatile(&w.value) };
+
+            if w.version.cnt() == ver0 {
+                self.seen = ver0;
+                return value;
+            }
+        }
+    }
+
+    /// Gets if the value has been changed.
+    /// * Returns `true` if a value has been sent since the last reception.
+    pub fn has_changed(&self) -> bool
+    {
+        let w = unsafe { self.w.as_mut().bk_unwrap() };
+
+        w.version.cnt() != self.seen
+    }
+
+    /// Gets a value.
+    /// * Returns the newest value, which is then regarded as received.
+    pub fn get(&mut self) -> M
+    {
+        self.read()
+    }
+
+    /// Receives a changed value.
+    /// * Returns the newest value.
+    /// * Blocks until a value is sent since the last reception.
+    pub fn changed(&mut self) -> M
+    {
+        let w = unsafe { self.w.as_mut().bk_unwrap() };
+
+        while w.version.cnt() == self.seen {
+            Minimult::wait(&w.version, MTEventCond::NotEqual(self.seen));
+        }
+
+        self.read()
+    }
+
+    /// Receives a changed value with a timeout.
+    /// * `ticks` - number of `Minimult::tick`s until the timeout.
+    /// * Returns the newest value.
+    /// * Blocks until a value is sent since the last reception.
+    /// * Returns `Err(MTTimeout)` if no value is sent within the timeout.
+    pub fn changed_timeout(&mut self, ticks: usize) -> Result<M, MTTimeout>
+    {
+        let w = unsafe { self.w.as_mut().bk_unwrap() };
+
+        let from = Minimult::tick_count();
+
+        loop {
+            if w.version.cnt() != self.seen {
+                return Ok(self.read());
+            }
+
+            if !Minimult::wait_timeout(&w.version, MTEventCond::NotEqual(self.seen), from, ticks) {
+                return Err(MTTimeout(()));
+            }
+        }
+    }
+}